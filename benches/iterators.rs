@@ -0,0 +1,52 @@
+#![feature(test)]
+
+extern crate test;
+
+use hdrhistogram::*;
+use test::{black_box, Bencher};
+
+#[bench]
+fn iter_recorded_sparse_wide(b: &mut Bencher) {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 5).unwrap();
+    h.record(1).unwrap();
+    h.record(1_000_000_000).unwrap();
+    h.record(u64::MAX).unwrap();
+
+    b.iter(|| black_box(h.iter_recorded().count()))
+}
+
+#[bench]
+fn iter_all_sparse_wide(b: &mut Bencher) {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 5).unwrap();
+    h.record(1).unwrap();
+    h.record(1_000_000_000).unwrap();
+    h.record(u64::MAX).unwrap();
+
+    b.iter(|| black_box(h.iter_all().count()))
+}
+
+#[bench]
+fn iter_recorded_dense_u128(b: &mut Bencher) {
+    let h = dense_u128();
+
+    b.iter(|| black_box(h.iter_recorded().map(|v| v.count_at_value()).sum::<u128>()))
+}
+
+#[bench]
+fn for_each_recorded_dense_u128(b: &mut Bencher) {
+    let h = dense_u128();
+
+    b.iter(|| {
+        let mut total = 0_u128;
+        h.for_each_recorded(|_, count, _| total += count);
+        black_box(total)
+    })
+}
+
+fn dense_u128() -> Histogram<u128> {
+    let mut h = Histogram::<u128>::new_with_bounds(1, 1_000_000, 3).unwrap();
+    for v in 0..1_000_000 {
+        h.record(v).unwrap();
+    }
+    h
+}
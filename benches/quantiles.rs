@@ -17,3 +17,31 @@ fn quantiles_below(b: &mut Bencher) {
         black_box(h.quantile_below(black_box(90_000)));
     })
 }
+
+#[bench]
+fn quantiles_below_cumulative(b: &mut Bencher) {
+    let mut h = Histogram::<u32>::new_with_bounds(1, 100_000, 3).unwrap();
+    for i in 0..100_000 {
+        h.record(i).unwrap();
+    }
+    let cumulative = h.cumulative_counts();
+
+    b.iter(|| {
+        black_box(cumulative.quantile_below(black_box(10)));
+        black_box(cumulative.quantile_below(black_box(90_000)));
+    })
+}
+
+#[bench]
+fn value_at_quantile_cumulative(b: &mut Bencher) {
+    let mut h = Histogram::<u32>::new_with_bounds(1, 100_000, 3).unwrap();
+    for i in 0..100_000 {
+        h.record(i).unwrap();
+    }
+    let cumulative = h.cumulative_counts();
+
+    b.iter(|| {
+        black_box(cumulative.value_at_quantile(black_box(0.5)));
+        black_box(cumulative.value_at_quantile(black_box(0.999)));
+    })
+}
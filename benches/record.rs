@@ -3,7 +3,7 @@
 extern crate test;
 
 use hdrhistogram::*;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use test::Bencher;
 
 use self::rand_varint::*;
@@ -32,6 +32,20 @@ fn record_precalc_random_values_with_1_count_u64(b: &mut Bencher) {
     })
 }
 
+#[bench]
+fn record_precalc_small_random_values_with_1_count_u64(b: &mut Bencher) {
+    // a small histogram that stays in cache, so the cost of recording itself dominates
+    let mut h = Histogram::<u64>::new_with_bounds(1, 100_000, 3).unwrap();
+    let mut rng = rand::rngs::SmallRng::from_entropy();
+    let values: Vec<u64> = (0..1_000_000).map(|_| rng.gen_range(0..100_000)).collect();
+
+    b.iter(|| {
+        for v in values.iter() {
+            h.record(*v).unwrap()
+        }
+    })
+}
+
 #[bench]
 fn record_precalc_random_values_with_max_count_u64(b: &mut Bencher) {
     let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
@@ -114,6 +128,28 @@ fn add_precalc_random_value_max_count_different_precision_u64(b: &mut Bencher) {
     })
 }
 
+#[bench]
+fn record_n_precalc_random_pairs_u64(b: &mut Bencher) {
+    let pairs = random_pairs();
+    b.iter(|| {
+        let mut h = Histogram::<u64>::new(3).unwrap();
+        for &(v, c) in pairs.iter() {
+            h.record_n(v, c).unwrap();
+        }
+        h
+    })
+}
+
+#[bench]
+fn record_pairs_bulk_precalc_random_pairs_u64(b: &mut Bencher) {
+    let pairs = random_pairs();
+    b.iter(|| {
+        let mut h = Histogram::<u64>::new(3).unwrap();
+        h.record_pairs_bulk(&pairs).unwrap();
+        h
+    })
+}
+
 #[bench]
 fn subtract_precalc_random_value_1_count_same_dimensions_u64(b: &mut Bencher) {
     do_subtract_benchmark(b, 1, || {
@@ -121,6 +157,22 @@ fn subtract_precalc_random_value_1_count_same_dimensions_u64(b: &mut Bencher) {
     })
 }
 
+#[bench]
+fn subtract_min_sparse_wide_u64(b: &mut Bencher) {
+    // subtracting the min forces a restat, which only needs to scan up to the max
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 5).unwrap();
+    h.record_n(1, 2).unwrap();
+    h.record(1_000).unwrap();
+    h.record(1_000_000).unwrap();
+    let mut subtrahend = Histogram::<u64>::new_from(&h);
+    subtrahend.record(1).unwrap();
+
+    b.iter(|| {
+        h.subtract(&subtrahend).unwrap();
+        h.record(1).unwrap();
+    })
+}
+
 // can't do subtraction with max count because it will error after the first iteration because
 // subtrahend count exceeds minuend. Similarly, when subtracting a different precision, the same
 // issue happens because the smallest equivalent value in the lower precision can map to a different
@@ -180,3 +232,11 @@ fn do_add_benchmark<F: Fn() -> Histogram<u64>>(
         }
     })
 }
+
+fn random_pairs() -> Vec<(u64, u64)> {
+    let mut rng = rand::rngs::SmallRng::from_entropy();
+    RandomVarintEncodedLengthIter::new(&mut rng)
+        .take(100_000)
+        .map(|v| (v, 3))
+        .collect()
+}
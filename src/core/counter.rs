@@ -20,6 +20,21 @@ pub trait Counter:
     fn as_f64(&self) -> f64;
     /// Counter as a u64.
     fn as_u64(&self) -> u64;
+
+    /// The largest value this counter can hold, at which counts saturate.
+    ///
+    /// The default implementation finds it by doubling until saturation; implementations should
+    /// override it with a constant.
+    fn max_value() -> Self {
+        let mut max = Self::one();
+        loop {
+            let next = max.saturating_add(max).saturating_add(Self::one());
+            if next == max {
+                return max;
+            }
+            max = next;
+        }
+    }
 }
 
 impl Counter for u8 {
@@ -31,6 +46,10 @@ impl Counter for u8 {
     fn as_u64(&self) -> u64 {
         u64::from(*self)
     }
+    #[inline]
+    fn max_value() -> Self {
+        u8::MAX
+    }
 }
 
 impl Counter for u16 {
@@ -42,6 +61,10 @@ impl Counter for u16 {
     fn as_u64(&self) -> u64 {
         u64::from(*self)
     }
+    #[inline]
+    fn max_value() -> Self {
+        u16::MAX
+    }
 }
 
 impl Counter for u32 {
@@ -53,6 +76,10 @@ impl Counter for u32 {
     fn as_u64(&self) -> u64 {
         u64::from(*self)
     }
+    #[inline]
+    fn max_value() -> Self {
+        u32::MAX
+    }
 }
 
 impl Counter for u64 {
@@ -64,4 +91,30 @@ impl Counter for u64 {
     fn as_u64(&self) -> u64 {
         *self
     }
+    #[inline]
+    fn max_value() -> Self {
+        u64::MAX
+    }
+}
+
+/// `u128` counts can exceed what the histogram's `u64` total count can represent, even in a single
+/// bucket. `as_u64` saturates at `u64::max_value()` in that case, and so does the total count
+/// (see `Histogram::total_count_saturated`).
+impl Counter for u128 {
+    #[inline]
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+    #[inline]
+    fn as_u64(&self) -> u64 {
+        if *self > u128::from(u64::MAX) {
+            u64::MAX
+        } else {
+            *self as u64
+        }
+    }
+    #[inline]
+    fn max_value() -> Self {
+        u128::MAX
+    }
 }
@@ -12,7 +12,11 @@ pub enum CreationError {
     LowExceedsMax,
     /// Highest trackable value must be >= 2 * lowest discernible value for some internal
     /// calculations to work out. In practice, high is typically much higher than 2 * low.
-    HighLessThanTwiceLow,
+    HighLessThanTwiceLow {
+        /// The smallest highest trackable value that would be accepted for the given lowest
+        /// discernible value, i.e. `2 * low`.
+        min_high: u64,
+    },
     /// Number of significant digits must be in the range `[0, 5]`. It is capped at 5 because 5
     /// significant digits is already more than almost anyone needs, and memory usage scales
     /// exponentially as this increases.
@@ -28,6 +32,9 @@ pub enum CreationError {
     /// The `usize` type is too small to represent the desired configuration. Use fewer significant
     /// figures or a lower max.
     UsizeTypeTooSmall,
+    /// The histogram's counts array would take more memory than the given budget. Use fewer
+    /// significant figures, a lower max, or a smaller counter type.
+    ExceedsMemoryBudget,
 }
 
 // TODO like RecordError, this is also an awkward split along resizing.
@@ -41,6 +48,12 @@ pub enum AdditionError {
     /// not expressible for `usize`. Configure this histogram to use fewer significant digits. Only
     /// possible when resize is enabled.
     ResizeFailedUsizeTypeTooSmall,
+    /// Adding the other histogram's counts would overflow a bucket count. Only returned by
+    /// `add_checked`.
+    CountOverflow,
+    /// Adding the other histogram's counts would make the total count exceed `u64::max_value()`.
+    /// Only returned by `add_checked`.
+    TotalCountOverflow,
 }
 
 /// Errors that can occur when subtracting another histogram.
@@ -53,6 +66,13 @@ pub enum SubtractionError {
     /// counts cannot go negative. The subtraction may have been partially applied to some counts as
     /// this error is returned when the first impossible subtraction is detected.
     SubtrahendCountExceedsMinuendCount,
+    /// The other histogram includes values that would map to indexes in this histogram that are
+    /// not expressible for `usize`. Configure this histogram to use fewer significant digits. Only
+    /// possible when resize is enabled.
+    ResizeFailedUsizeTypeTooSmall,
+    /// The other histogram includes a value that does not map to any bucket in this histogram.
+    /// Only possible when the two histograms are configured differently.
+    SubtrahendValueNotRepresentable,
 }
 
 // TODO the error conditions here are awkward: one only possible when resize is disabled, the other
@@ -68,6 +88,22 @@ pub enum RecordError {
     /// cannot be resized because `usize` cannot represent sufficient length. Configure this
     /// histogram to use fewer significant digits. Only possible when resizing is enabled.
     ResizeFailedUsizeTypeTooSmall,
+    /// Recording the count would make the histogram's total count exceed `u64::max_value()`. Only
+    /// returned by `record_n_total_checked`.
+    TotalCountOverflow,
+    /// The weight to record is negative, infinite, or NaN. Only returned by `record_weighted`.
+    InvalidWeight,
+}
+
+/// Errors that can occur when creating a histogram from a snapshot of its counts.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum SnapshotError {
+    /// The histogram could not be created with the snapshot's configuration.
+    CreationError(CreationError),
+    /// The counts array is longer than it should be for the snapshot's value range.
+    CountsArrayTooLong,
+    /// A count exceeded what can be represented in the chosen counter type.
+    UnsuitableCounterType,
 }
 
 #[allow(missing_docs)]
@@ -79,10 +115,11 @@ impl fmt::Display for CreationError {
         match self {
             CreationError::LowIsZero => write!(f, "Lowest discernible value must be >= 1"),
             CreationError::LowExceedsMax => write!(f, "Lowest discernible value must be <= `u64::max_value() / 2`"),
-            CreationError::HighLessThanTwiceLow => write!(f, "Highest trackable value must be >= 2 * lowest discernible value for some internal calculations"),
+            CreationError::HighLessThanTwiceLow { min_high } => write!(f, "Highest trackable value must be >= 2 * lowest discernible value ({}) for some internal calculations", min_high),
             CreationError::SigFigExceedsMax => write!(f, "Number of significant digits must be in the range `[0, 5]`"),
             CreationError::CannotRepresentSigFigBeyondLow => write!(f, "Cannot represent sigfig worth of values beyond the lowest discernible value"),
             CreationError::UsizeTypeTooSmall =>  write!(f, "The `usize` type is too small to represent the desired configuration"),
+            CreationError::ExceedsMemoryBudget => write!(f, "The histogram's counts array would take more memory than the given budget"),
         }
     }
 }
@@ -94,6 +131,8 @@ impl fmt::Display for AdditionError {
         match self {
             AdditionError::OtherAddendValueExceedsRange => write!(f, "The other histogram includes values that do not fit in this histogram's range"),
             AdditionError::ResizeFailedUsizeTypeTooSmall => write!(f, "The other histogram includes values that would map to indexes in this histogram that are not expressible for `usize`"),
+            AdditionError::CountOverflow => write!(f, "Adding the other histogram's counts would overflow a bucket count"),
+            AdditionError::TotalCountOverflow => write!(f, "Adding the other histogram's counts would make the total count exceed `u64::max_value()`"),
         }
     }
 }
@@ -105,6 +144,8 @@ impl fmt::Display for SubtractionError {
         match self {
             SubtractionError::SubtrahendValueExceedsMinuendRange => write!(f, "The other histogram includes values that do not fit in this histogram's range"),
             SubtractionError::SubtrahendCountExceedsMinuendCount => write!(f, "The other histogram includes counts that are higher than the current count for a value, and counts cannot go negative"),
+            SubtractionError::ResizeFailedUsizeTypeTooSmall => write!(f, "The other histogram includes values that would map to indexes in this histogram that are not expressible for `usize`"),
+            SubtractionError::SubtrahendValueNotRepresentable => write!(f, "The other histogram includes a value that does not map to any bucket in this histogram"),
         }
     }
 }
@@ -116,12 +157,41 @@ impl fmt::Display for RecordError {
         match self {
             RecordError::ValueOutOfRangeResizeDisabled  => write!(f, "The value to record is not representable in this histogram and resizing is disabled"),
             RecordError::ResizeFailedUsizeTypeTooSmall => write!(f, "Auto resizing is enabled and must be used to represent the provided value, but the histogram cannot be resized because `usize` cannot represent sufficient length"),
+            RecordError::TotalCountOverflow => write!(f, "Recording the count would make the histogram's total count exceed `u64::max_value()`"),
+            RecordError::InvalidWeight => write!(f, "The weight to record is negative, infinite, or NaN"),
         }
     }
 }
 
 impl Error for RecordError {}
 
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::CreationError(e) => {
+                write!(f, "The histogram could not be created: {}", e)
+            }
+            SnapshotError::CountsArrayTooLong => write!(
+                f,
+                "The counts array is longer than it should be for the value range"
+            ),
+            SnapshotError::UnsuitableCounterType => write!(
+                f,
+                "A count exceeded what can be represented in the chosen counter type"
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::CreationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for UsizeTypeTooSmall {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -1,5 +1,5 @@
 use crate::core::counter::Counter;
-use crate::iterators::{HistogramIterator, PickMetadata, PickyIterator};
+use crate::iterators::{HistogramIterator, IterationValue, PickMetadata, PickyIterator};
 use crate::Histogram;
 
 /// An iterator that will yield every bin.
@@ -29,3 +29,38 @@ impl<T: Counter> PickyIterator<T> for Iter {
         true
     }
 }
+
+impl<'a, T: Counter> DoubleEndedIterator for HistogramIterator<'a, T, Iter> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // indices are picked in order from the front, so everything up to the last picked one
+        // has already been yielded
+        let front_index = self.last_picked_index.map_or(0, |i| i + 1);
+        if self.back_index <= front_index {
+            return None;
+        }
+
+        self.back_index -= 1;
+        let count_at_index = self
+            .hist
+            .count_at_index(self.back_index)
+            .expect("back index cannot exceed counts length");
+
+        // the cumulative count up to and including this index is whatever isn't above it
+        let total_count_to_index = self.hist.len().saturating_sub(self.count_from_back_index);
+        self.count_from_back_index = self
+            .count_from_back_index
+            .saturating_add(count_at_index.as_u64());
+
+        let quantile = total_count_to_index as f64 / self.hist.len() as f64;
+        let value = self.hist.value_for(self.back_index);
+        Some(IterationValue {
+            value_iterated_to: self.hist.highest_equivalent(value),
+            quantile,
+            quantile_iterated_to: quantile,
+            count_at_value: count_at_index,
+            count_since_last_iteration: count_at_index.as_u64(),
+            value_sum_since_last_iteration: u128::from(self.hist.median_equivalent(value))
+                * u128::from(count_at_index.as_u64()),
+        })
+    }
+}
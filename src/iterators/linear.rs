@@ -14,26 +14,26 @@ pub struct Iter<'a, T: 'a + Counter> {
 
 impl<'a, T: 'a + Counter> Iter<'a, T> {
     /// Construct a new linear iterator. See `Histogram::iter_linear` for details.
+    ///
+    /// If `value_units_per_bucket` is 0, the steps would never advance, so the iterator yields
+    /// nothing.
     pub fn new(
         hist: &'a Histogram<T>,
         value_units_per_bucket: u64,
     ) -> HistogramIterator<'a, T, Iter<'a, T>> {
-        assert!(
-            value_units_per_bucket > 0,
-            "value_units_per_bucket must be > 0"
-        );
-
-        let new_lowest = hist.lowest_equivalent(value_units_per_bucket - 1);
-        HistogramIterator::new(
+        let highest = value_units_per_bucket.saturating_sub(1);
+        let picker = Iter {
             hist,
-            Iter {
-                hist,
-                value_units_per_bucket,
-                // won't underflow because value_units_per_bucket > 0
-                current_step_highest_value_reporting_level: value_units_per_bucket - 1,
-                current_step_lowest_value_reporting_level: new_lowest,
-            },
-        )
+            value_units_per_bucket,
+            current_step_highest_value_reporting_level: highest,
+            current_step_lowest_value_reporting_level: hist.lowest_equivalent(highest),
+        };
+
+        if value_units_per_bucket > 0 {
+            HistogramIterator::new(hist, picker)
+        } else {
+            HistogramIterator::ended(hist, picker)
+        }
     }
 }
 
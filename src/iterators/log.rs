@@ -17,28 +17,31 @@ pub struct Iter<'a, T: 'a + Counter> {
 
 impl<'a, T: 'a + Counter> Iter<'a, T> {
     /// Construct a new logarithmic iterator. See `Histogram::iter_log` for details.
+    ///
+    /// If `value_units_in_first_bucket` is 0 or `log_base` is not greater than 1.0, the steps
+    /// would never grow, so the iterator yields nothing.
     pub fn new(
         hist: &'a Histogram<T>,
         value_units_in_first_bucket: u64,
         log_base: f64,
     ) -> HistogramIterator<'a, T, Iter<'a, T>> {
-        assert!(
-            value_units_in_first_bucket > 0,
-            "value_units_per_bucket must be > 0"
-        );
-        assert!(log_base > 1.0, "log_base must be > 1.0");
+        // also rejects NaN
+        let valid = value_units_in_first_bucket > 0 && log_base > 1.0;
 
-        let new_lowest = hist.lowest_equivalent(value_units_in_first_bucket - 1);
-        HistogramIterator::new(
+        let highest = value_units_in_first_bucket.saturating_sub(1);
+        let picker = Iter {
             hist,
-            Iter {
-                hist,
-                log_base,
-                next_value_reporting_level: value_units_in_first_bucket as f64,
-                current_step_highest_value_reporting_level: value_units_in_first_bucket - 1,
-                current_step_lowest_value_reporting_level: new_lowest,
-            },
-        )
+            log_base,
+            next_value_reporting_level: value_units_in_first_bucket as f64,
+            current_step_highest_value_reporting_level: highest,
+            current_step_lowest_value_reporting_level: hist.lowest_equivalent(highest),
+        };
+
+        if valid {
+            HistogramIterator::new(hist, picker)
+        } else {
+            HistogramIterator::ended(hist, picker)
+        }
     }
 }
 
@@ -29,7 +29,15 @@ pub struct PickMetadata {
 }
 
 impl PickMetadata {
-    fn new(quantile_iterated_to: Option<f64>, value_iterated_to: Option<u64>) -> PickMetadata {
+    /// Create a new PickMetadata.
+    ///
+    /// `quantile_iterated_to` is the quantile iterated to by this pick, if the picker knows it
+    /// (like the quantile iterator does). If `None`, the quantile of the picked bucket is used.
+    ///
+    /// `value_iterated_to` is the value iterated to by this pick, if the picker can supply a more
+    /// useful value than the largest value in the picked bucket (like the linear and logarithmic
+    /// iterators do). If `None`, the largest value in the bucket is used.
+    pub fn new(quantile_iterated_to: Option<f64>, value_iterated_to: Option<u64>) -> PickMetadata {
         PickMetadata {
             quantile_iterated_to,
             value_iterated_to,
@@ -38,6 +46,9 @@ impl PickMetadata {
 }
 
 /// A trait for designing an subset iterator over values in a `Histogram`.
+///
+/// Implement this to define a custom iteration strategy, and iterate with it using
+/// `Histogram::iter_with_picker`.
 pub trait PickyIterator<T: Counter> {
     /// Return `Some` if an `IterationValue` should be emitted at this point.
     ///
@@ -59,6 +70,14 @@ pub trait PickyIterator<T: Counter> {
     /// picked, even if the index was not advanced in the last iteration (because `pick()` returned
     /// `Some`).
     fn more(&mut self, index_to_pick: usize) -> bool;
+
+    /// Does this picker never pick an index with a zero count?
+    ///
+    /// If so, the iterator is free to jump straight over runs of zero counts rather than
+    /// presenting each of those indices to `pick()`, which is much faster for sparse histograms.
+    fn skips_zero_counts(&self) -> bool {
+        false
+    }
 }
 
 /// `HistogramIterator` provides a base iterator for a `Histogram`.
@@ -72,10 +91,16 @@ pub struct HistogramIterator<'a, T: 'a + Counter, P: PickyIterator<T>> {
     hist: &'a Histogram<T>,
     total_count_to_index: u64,
     count_since_last_iteration: u64,
+    value_sum_since_last_iteration: u128,
     count_at_index: T,
     current_index: usize,
     last_picked_index: Option<usize>,
     max_value_index: usize,
+    // exclusive upper bound of indices left to iterate over; only lowered when iterating from the
+    // back, which only the `all` iterator supports
+    back_index: usize,
+    // total count at indices >= back_index
+    count_from_back_index: u64,
     fresh: bool,
     ended: bool,
     picker: P,
@@ -89,16 +114,36 @@ pub struct IterationValue<T: Counter> {
     quantile_iterated_to: f64,
     count_at_value: T,
     count_since_last_iteration: u64,
+    value_sum_since_last_iteration: u128,
 }
 
 impl<T: Counter> IterationValue<T> {
-    /// Create a new IterationValue.
+    /// Create a new IterationValue, with a `value_sum_since_last_iteration` of 0.
     pub fn new(
         value_iterated_to: u64,
         quantile: f64,
         quantile_iterated_to: f64,
         count_at_value: T,
         count_since_last_iteration: u64,
+    ) -> IterationValue<T> {
+        IterationValue::new_with_value_sum(
+            value_iterated_to,
+            quantile,
+            quantile_iterated_to,
+            count_at_value,
+            count_since_last_iteration,
+            0,
+        )
+    }
+
+    /// Create a new IterationValue, including its `value_sum_since_last_iteration`.
+    pub fn new_with_value_sum(
+        value_iterated_to: u64,
+        quantile: f64,
+        quantile_iterated_to: f64,
+        count_at_value: T,
+        count_since_last_iteration: u64,
+        value_sum_since_last_iteration: u128,
     ) -> IterationValue<T> {
         IterationValue {
             value_iterated_to,
@@ -106,6 +151,7 @@ impl<T: Counter> IterationValue<T> {
             quantile_iterated_to,
             count_at_value,
             count_since_last_iteration,
+            value_sum_since_last_iteration,
         }
     }
 
@@ -142,23 +188,42 @@ impl<T: Counter> IterationValue<T> {
     pub fn count_since_last_iteration(&self) -> u64 {
         self.count_since_last_iteration
     }
+
+    /// Sum of the values traversed since the last iteration step, with each value represented by
+    /// the `median_equivalent` of its bucket, saturating at `u128::max_value()`.
+    ///
+    /// This is handy for per-window totals, e.g. for the bars of a bar chart drawn with the linear
+    /// or logarithmic iterators.
+    pub fn value_sum_since_last_iteration(&self) -> u128 {
+        self.value_sum_since_last_iteration
+    }
 }
 
 impl<'a, T: Counter, P: PickyIterator<T>> HistogramIterator<'a, T, P> {
-    fn new(h: &'a Histogram<T>, picker: P) -> HistogramIterator<'a, T, P> {
+    pub(crate) fn new(h: &'a Histogram<T>, picker: P) -> HistogramIterator<'a, T, P> {
         HistogramIterator {
             hist: h,
             total_count_to_index: 0,
             count_since_last_iteration: 0,
+            value_sum_since_last_iteration: 0,
             count_at_index: T::zero(),
             current_index: 0,
             last_picked_index: None,
             max_value_index: h.index_for(h.max()).expect("Either 0 or an existing index"),
+            back_index: h.distinct_values(),
+            count_from_back_index: 0,
             picker,
             fresh: true,
             ended: false,
         }
     }
+
+    /// An iterator that yields nothing, for pickers given parameters they can't iterate with.
+    fn ended(h: &'a Histogram<T>, picker: P) -> HistogramIterator<'a, T, P> {
+        let mut iter = Self::new(h, picker);
+        iter.ended = true;
+        iter
+    }
 }
 
 impl<'a, T: 'a, P> Iterator for HistogramIterator<'a, T, P>
@@ -179,8 +244,8 @@ where
         // called self.next() again at the bottom. instead, we loop when we would have yielded None
         // unless we have ended.
         while !self.ended {
-            // have we reached the end?
-            if self.current_index == self.hist.distinct_values() {
+            // have we reached the end (or what has already been iterated from the back)?
+            if self.current_index >= self.back_index {
                 self.ended = true;
                 return None;
             }
@@ -197,6 +262,18 @@ where
                 assert!(self.current_index < self.hist.distinct_values());
 
                 if self.fresh {
+                    if self.picker.skips_zero_counts() {
+                        // jump to the next non-zero count; zero counts don't change any totals
+                        if let Some(offset) = self
+                            .hist
+                            .counts
+                            .get(self.current_index..=self.max_value_index)
+                            .and_then(|counts| counts.iter().position(|&c| c != T::zero()))
+                        {
+                            self.current_index += offset;
+                        }
+                    }
+
                     // at a new index, and not past the max, so there's nonzero counts to add
                     self.count_at_index = self
                         .hist
@@ -209,6 +286,15 @@ where
                     self.count_since_last_iteration = self
                         .count_since_last_iteration
                         .saturating_add(self.count_at_index.as_u64());
+                    if self.count_at_index != T::zero() {
+                        let value = self
+                            .hist
+                            .median_equivalent(self.hist.value_for(self.current_index));
+                        self.value_sum_since_last_iteration =
+                            self.value_sum_since_last_iteration.saturating_add(
+                                u128::from(value) * u128::from(self.count_at_index.as_u64()),
+                            );
+                    }
 
                     // make sure we don't add this index again
                     self.fresh = false;
@@ -234,6 +320,7 @@ where
                         .count_at_index(self.current_index)
                         .expect("current index cannot exceed counts length"),
                     count_since_last_iteration: self.count_since_last_iteration,
+                    value_sum_since_last_iteration: self.value_sum_since_last_iteration,
                 };
 
                 // Note that we *don't* increment self.current_index here. The picker will be
@@ -243,6 +330,7 @@ where
                 // step multiple times without advancing the index.
 
                 self.count_since_last_iteration = 0;
+                self.value_sum_since_last_iteration = 0;
                 self.last_picked_index = Some(self.current_index);
                 return Some(val);
             }
@@ -7,6 +7,7 @@ pub struct Iter<'a, T: 'a + Counter> {
     hist: &'a Histogram<T>,
     ticks_per_half_distance: u32,
     quantile_to_iterate_to: f64,
+    end_quantile: f64,
     reached_end: bool,
 }
 
@@ -16,9 +17,22 @@ impl<'a, T: 'a + Counter> Iter<'a, T> {
         hist: &'a Histogram<T>,
         ticks_per_half_distance: u32,
     ) -> HistogramIterator<'a, T, Iter<'a, T>> {
+        Self::new_between(hist, ticks_per_half_distance, 0.0, 1.0)
+    }
+
+    /// Construct a new iterator over a quantile sub-range. See `Histogram::iter_quantiles_between`
+    /// for details.
+    pub fn new_between(
+        hist: &'a Histogram<T>,
+        ticks_per_half_distance: u32,
+        start_quantile: f64,
+        end_quantile: f64,
+    ) -> HistogramIterator<'a, T, Iter<'a, T>> {
+        // zero ticks would never advance the quantile, so treat it as the smallest useful value
+        let ticks_per_half_distance = ticks_per_half_distance.max(1);
         assert!(
-            ticks_per_half_distance > 0,
-            "Ticks per half distance must be > 0"
+            0.0 <= start_quantile && start_quantile < end_quantile && end_quantile <= 1.0,
+            "Quantile range must satisfy 0.0 <= start < end <= 1.0"
         );
 
         HistogramIterator::new(
@@ -26,7 +40,8 @@ impl<'a, T: 'a + Counter> Iter<'a, T> {
             Iter {
                 hist,
                 ticks_per_half_distance,
-                quantile_to_iterate_to: 0.0,
+                quantile_to_iterate_to: start_quantile,
+                end_quantile,
                 reached_end: false,
             },
         )
@@ -93,13 +108,15 @@ impl<'a, T: 'a + Counter> PickyIterator<T> for Iter<'a, T> {
         // where `quantile_to_iterate_to` has been set to 1.0.
         // #3, #4 last phase: Similar, but iteration proceeded normally up to 1.0 without any
         // last-bucket skipping because it wasn't at the last bucket.
-        if self.quantile_to_iterate_to == 1.0 {
+        //
+        // When iterating over a quantile sub-range, the end quantile plays the role of 1.0.
+        if self.quantile_to_iterate_to >= self.end_quantile {
             // We want to pick this value but not do the math below because it doesn't work when
             // quantile >= 1.0.
             //
             // We also want to prevent any further iteration.
             self.reached_end = true;
-            return Some(PickMetadata::new(Some(1.0), None));
+            return Some(PickMetadata::new(Some(self.end_quantile), None));
         }
 
         // #2, first phase:
@@ -150,7 +167,10 @@ impl<'a, T: 'a + Counter> PickyIterator<T> for Iter<'a, T> {
             // change an f64 slightly smaller than 1.0, so just short circuit to 1.0.
             // This happens easily in case #4, and plausibly in #3: it will iterate up to 1.0
             // without any skipping, which will
-            1.0
+            self.end_quantile
+        } else if sum > self.end_quantile {
+            // don't step past the end of a quantile sub-range
+            self.end_quantile
         } else {
             sum
         };
@@ -165,9 +185,9 @@ impl<'a, T: 'a + Counter> PickyIterator<T> for Iter<'a, T> {
 
         // #2, middle phase: already picked the max-value index once with iteration quantile < 1.0,
         // and `more()` is now called (for the first time), so iterate one more time, but jump to
-        // quantile 1.0 while doing so. We don't set `reached_end` here because we do want 1 more
-        // iteration.
-        self.quantile_to_iterate_to = 1.0;
+        // quantile 1.0 (or the end of the sub-range) while doing so. We don't set `reached_end`
+        // here because we do want 1 more iteration.
+        self.quantile_to_iterate_to = self.end_quantile;
         true
     }
 }
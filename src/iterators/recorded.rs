@@ -33,4 +33,70 @@ impl<T: Counter> PickyIterator<T> for Iter {
         // can't any more bins to yield.
         false
     }
+
+    fn skips_zero_counts(&self) -> bool {
+        true
+    }
+}
+
+/// Which value in a bucket to report as the value iterated to, for `Histogram::iter_recorded_repr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueRepr {
+    /// The lowest value equivalent to the bucket, per `Histogram::lowest_equivalent`.
+    Lowest,
+    /// The middle of the bucket's range, per `Histogram::median_equivalent`.
+    Median,
+    /// The highest value equivalent to the bucket, per `Histogram::highest_equivalent`. This is
+    /// what `Histogram::iter_recorded` reports.
+    Highest,
+}
+
+/// An iterator that will yield only bins with at least one sample, reporting a chosen
+/// representative value for each.
+pub struct ReprIter<'a, T: 'a + Counter> {
+    hist: &'a Histogram<T>,
+    repr: ValueRepr,
+    visited: Option<usize>,
+}
+
+impl<'a, T: 'a + Counter> ReprIter<'a, T> {
+    /// Construct a new sampled iterator. See `Histogram::iter_recorded_repr` for details.
+    pub fn new(
+        hist: &'a Histogram<T>,
+        repr: ValueRepr,
+    ) -> HistogramIterator<'a, T, ReprIter<'a, T>> {
+        HistogramIterator::new(
+            hist,
+            ReprIter {
+                hist,
+                repr,
+                visited: None,
+            },
+        )
+    }
+}
+
+impl<'a, T: 'a + Counter> PickyIterator<T> for ReprIter<'a, T> {
+    fn pick(&mut self, index: usize, _: u64, count_at_index: T) -> Option<PickMetadata> {
+        if count_at_index != T::zero() && self.visited.map(|i| i != index).unwrap_or(true) {
+            self.visited = Some(index);
+            let value = self.hist.value_for(index);
+            let value = match self.repr {
+                ValueRepr::Lowest => self.hist.lowest_equivalent(value),
+                ValueRepr::Median => self.hist.median_equivalent(value),
+                ValueRepr::Highest => self.hist.highest_equivalent(value),
+            };
+            return Some(PickMetadata::new(None, Some(value)));
+        }
+        None
+    }
+
+    fn more(&mut self, _: usize) -> bool {
+        // like `Iter`, we never visit empty bins, so there's never more to yield
+        false
+    }
+
+    fn skips_zero_counts(&self) -> bool {
+        true
+    }
 }
@@ -207,7 +207,11 @@ extern crate nom;
 use num_traits::ToPrimitive;
 use std::borrow::Borrow;
 use std::cmp;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::collections::TryReserveError;
+use std::io;
+use std::num::NonZeroU64;
+use std::ops::{self, Add, AddAssign, Sub, SubAssign};
+use std::time;
 
 use iterators::HistogramIterator;
 
@@ -254,9 +258,13 @@ const ORIGINAL_MAX: u64 = 0;
 #[derive(Debug, Clone)]
 pub struct Histogram<T: Counter> {
     auto_resize: bool,
+    // clamp out-of-range values on plain records instead of failing; never set with auto_resize
+    clamp_out_of_range: bool,
 
     // >= 2 * lowest_discernible_value
     highest_trackable_value: u64,
+    // highest_trackable_value as configured, before any auto-resizing
+    initial_highest_trackable_value: u64,
     // >= 1
     lowest_discernible_value: u64,
     // in [0, 5]
@@ -287,7 +295,26 @@ pub struct Histogram<T: Counter> {
     min_non_zero_value: u64,
 
     total_count: u64,
+    // set once total_count has saturated at u64::max_value()
+    total_count_saturated: bool,
     counts: Vec<T>,
+
+    // the time window covered by timestamped samples, if any
+    start_time: Option<time::SystemTime>,
+    end_time: Option<time::SystemTime>,
+    // when the current min and max were recorded with record_at, if they were
+    min_value_time: Option<time::SystemTime>,
+    max_value_time: Option<time::SystemTime>,
+
+    // samples clamped by the saturating record methods, below low and above high respectively
+    clamped_low_count: u64,
+    clamped_high_count: u64,
+
+    // self-measurement of recording, see record_count and resize_count
+    #[cfg(feature = "metrics")]
+    record_count: u64,
+    #[cfg(feature = "metrics")]
+    resize_count: u64,
 }
 
 /// Module containing the implementations of all `Histogram` iterators.
@@ -318,6 +345,18 @@ impl<T: Counter> Histogram<T> {
         self.significant_value_digits
     }
 
+    /// Returns true if this histogram has the given lowest discernible value, highest trackable
+    /// value, and significant value digits, i.e. if it is configured as if constructed with
+    /// `new_with_bounds(low, high, sigfig)`.
+    ///
+    /// Together with `is_empty`, this makes it cheap to find a reusable histogram in a pool. Note
+    /// that auto-resizing changes the highest trackable value (see `high`).
+    pub fn matches_config(&self, low: u64, high: u64, sigfig: u8) -> bool {
+        self.lowest_discernible_value == low
+            && self.highest_trackable_value == high
+            && self.significant_value_digits == sigfig
+    }
+
     /// Get the total number of samples recorded.
     #[deprecated(since = "6.0.0", note = "use `len` instead")]
     pub fn count(&self) -> u64 {
@@ -329,6 +368,158 @@ impl<T: Counter> Histogram<T> {
         self.total_count
     }
 
+    /// Returns true if the total number of samples recorded has exceeded `u64::max_value()`.
+    ///
+    /// The total count saturates rather than overflowing, so once this happens `len()` is no
+    /// longer accurate, and neither are quantile calculations like `value_at_quantile` and
+    /// `quantile_below`. This is cleared by `clear()` and `reset()`, or if the total count is
+    /// recalculated (e.g. after `subtract()`) and fits again.
+    pub fn total_count_saturated(&self) -> bool {
+        self.total_count_saturated
+    }
+
+    /// Get the earliest time a sample was recorded at with `record_at`, if any.
+    ///
+    /// This carries over to histograms created with `new_from` (and so `clone_correct`), and is
+    /// combined by `add`. It is cleared by `reset()`.
+    pub fn start_time(&self) -> Option<time::SystemTime> {
+        self.start_time
+    }
+
+    /// Get the latest time a sample was recorded at with `record_at`, if any.
+    ///
+    /// See `start_time` for how this is maintained.
+    pub fn end_time(&self) -> Option<time::SystemTime> {
+        self.end_time
+    }
+
+    /// Get the time at which the current minimum value (see `min`) was recorded with `record_at`.
+    ///
+    /// This is updated by `record_at` whenever it records a new minimum, and cleared by `reset()`.
+    /// Other ways of recording don't update it, so it is `None` if the minimum was never recorded
+    /// with `record_at`, and may be stale if a smaller value was since recorded without a time. It
+    /// is not carried over by `new_from` or combined by `add`.
+    pub fn min_value_time(&self) -> Option<time::SystemTime> {
+        self.min_value_time
+    }
+
+    /// Get the time at which the current maximum value (see `max`) was recorded with `record_at`.
+    ///
+    /// See `min_value_time` for how this is maintained.
+    pub fn max_value_time(&self) -> Option<time::SystemTime> {
+        self.max_value_time
+    }
+
+    /// Get the number of samples recorded with `saturating_record` (and its variants), or with
+    /// `record` (and its variants) under `OutOfRangePolicy::Clamp`, that were non-zero but below
+    /// `low()`, and so were recorded in the lowest bucket.
+    ///
+    /// This is a running tally over the lifetime of the histogram, and is cleared by `reset()`.
+    pub fn clamped_low_count(&self) -> u64 {
+        self.clamped_low_count
+    }
+
+    /// Get the number of samples recorded with `saturating_record` (and its variants), or with
+    /// `record` (and its variants) under `OutOfRangePolicy::Clamp`, that were above the highest
+    /// trackable value, and so were recorded as that value instead.
+    ///
+    /// See `clamped_low_count` for how this is maintained.
+    pub fn clamped_high_count(&self) -> u64 {
+        self.clamped_high_count
+    }
+
+    /// Get the number of record operations performed on this histogram since it was created, e.g.
+    /// one per call to `record` or `record_n` (including values backfilled by coordinated
+    /// omission correction), or one per pair for `record_pairs_bulk`. Failed records aren't
+    /// counted. Adding other histograms doesn't count as recording.
+    ///
+    /// Only available with the `metrics` feature, which is meant for measuring the library itself.
+    #[cfg(feature = "metrics")]
+    pub fn record_count(&self) -> u64 {
+        self.record_count
+    }
+
+    /// Get the number of times this histogram's counts array has grown since it was created, e.g.
+    /// by auto-resizing to record a large value or to add a histogram with a wider range.
+    ///
+    /// Recording never allocates in a histogram whose range covers all recorded values, so this
+    /// is useful to check that a configuration avoids resizing in steady state. Only available
+    /// with the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub fn resize_count(&self) -> u64 {
+        self.resize_count
+    }
+
+    /// Returns true if any bucket's count has reached `T::max_value()`.
+    ///
+    /// Bucket counts saturate rather than overflowing, so a saturated bucket may have lost counts,
+    /// and with it the total count and quantile calculations may be off. This is mostly a concern
+    /// with small counter types like `u8` or `u16`. Unlike `total_count_saturated`, this scans
+    /// every bucket.
+    pub fn has_saturated_bucket(&self) -> bool {
+        self.counts.contains(&T::max_value())
+    }
+
+    /// Compute a 64-bit fingerprint of this histogram's configuration and contents.
+    ///
+    /// Histograms that are equal (see the `PartialEq` implementation) have the same fingerprint,
+    /// even if their counts arrays have different lengths or counter types, and the fingerprint is
+    /// stable across runs and platforms. This makes it useful as a cheap pre-check before a full
+    /// comparison, e.g. for caching or deduplication. It is not a cryptographic hash: different
+    /// histograms can share a fingerprint, so a matching fingerprint does not imply equality.
+    ///
+    /// This still scans the whole counts array, so compute it once and keep it around.
+    pub fn fingerprint(&self) -> u64 {
+        // 64-bit FNV-1a
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        fn mix(hash: u64, n: u64) -> u64 {
+            n.to_le_bytes()
+                .iter()
+                .fold(hash, |h, &b| (h ^ u64::from(b)).wrapping_mul(PRIME))
+        }
+
+        let mut hash = OFFSET_BASIS;
+        hash = mix(hash, self.lowest_discernible_value);
+        hash = mix(hash, u64::from(self.significant_value_digits));
+        hash = mix(hash, self.total_count);
+        hash = mix(hash, self.max());
+        hash = mix(hash, self.min_nz());
+        for (i, c) in self.counts.iter().enumerate() {
+            if *c != T::zero() {
+                hash = mix(hash, i as u64);
+                hash = mix(hash, c.as_u64());
+            }
+        }
+        hash
+    }
+
+    /// Returns true if this histogram has the same configuration and per-bucket counts as `other`.
+    ///
+    /// Unlike `==`, this ignores the cached total count, max, and min, and only compares the
+    /// lowest discernible value, significant digits, and the count in each bucket. Buckets past
+    /// the end of the shorter counts array are treated as zero.
+    ///
+    /// This is the right comparison after lossy operations such as saturating adds: once a
+    /// bucket saturates, the total count keeps track of counts the bucket could not hold, so two
+    /// histograms with identical buckets may still have different totals and compare unequal with
+    /// `==`.
+    pub fn counts_eq(&self, other: &Histogram<T>) -> bool {
+        if self.lowest_discernible_value != other.lowest_discernible_value
+            || self.significant_value_digits != other.significant_value_digits
+        {
+            return false;
+        }
+
+        let (shorter, longer) = if self.counts.len() <= other.counts.len() {
+            (&self.counts, &other.counts)
+        } else {
+            (&other.counts, &self.counts)
+        };
+        shorter == &longer[..shorter.len()]
+            && longer[shorter.len()..].iter().all(|c| *c == T::zero())
+    }
+
     /// Returns true if this histogram has no recorded values.
     pub fn is_empty(&self) -> bool {
         self.total_count == 0
@@ -349,6 +540,18 @@ impl<T: Counter> Histogram<T> {
         self.auto_resize
     }
 
+    /// Returns how `record` and friends currently handle values outside the histogram's range.
+    /// See `on_out_of_range`.
+    pub fn out_of_range_policy(&self) -> OutOfRangePolicy {
+        if self.auto_resize {
+            OutOfRangePolicy::Resize
+        } else if self.clamp_out_of_range {
+            OutOfRangePolicy::Clamp
+        } else {
+            OutOfRangePolicy::Error
+        }
+    }
+
     // ********************************************************************************************
     // Methods for looking up the count for a given value/index
     // ********************************************************************************************
@@ -423,14 +626,34 @@ impl<T: Counter> Histogram<T> {
     /// corrective behavior is important.
     ///
     /// If `interval` is larger than 0, add auto-generated value records as appropriate if value is
-    /// larger than `interval`.
+    /// larger than `interval`. This never panics, whatever `interval` is: an `interval` of 0 or at
+    /// least half of the max value backfills nothing. Beware that a tiny `interval` relative to
+    /// the recorded values backfills a correspondingly huge number of values, which takes a long
+    /// time.
     pub fn clone_correct(&self, interval: u64) -> Histogram<T> {
+        self.clone_correct_counted(interval).0
+    }
+
+    /// Get a copy of this histogram, corrected for coordinated omission, along with the number of
+    /// auto-generated samples that were added by the correction.
+    ///
+    /// This is like `clone_correct`, but the number of synthetic samples gives some insight into
+    /// the magnitude of the correction. It saturates at `u64::max_value()`.
+    pub fn clone_correct_counted(&self, interval: u64) -> (Histogram<T>, u64) {
         let mut h = Histogram::new_from(self);
+        let mut synthetic: u64 = 0;
         for v in self.iter_recorded() {
-            h.record_n_correct(v.value_iterated_to(), v.count_at_value(), interval)
-                .expect("Same dimensions; all values should be representable");
+            // Clamping never errors. With the same dimensions every value fits anyway, since the
+            // backfilled values are all smaller than the recorded one.
+            let added = h
+                .record_n_correct_counted(v.value_iterated_to(), v.count_at_value(), interval, true)
+                .unwrap();
+            synthetic = synthetic.saturating_add(added);
         }
-        h
+        // the user didn't ask for clamping, so backfilled values below `low()` aren't tallied
+        h.clamped_low_count = 0;
+        h.clamped_high_count = 0;
+        (h, synthetic)
     }
 
     /// Overwrite this histogram with the given histogram. All data and statistics in this
@@ -459,11 +682,14 @@ impl<T: Counter> Histogram<T> {
     /// Add the contents of another histogram to this one.
     ///
     /// Returns an error if values in the other histogram cannot be stored; see `AdditionError`.
+    /// Adding an empty histogram never fails and returns without scanning its counts, whatever its
+    /// range, so this histogram isn't resized; only its time window is combined.
     pub fn add<B: Borrow<Histogram<T>>>(&mut self, source: B) -> Result<(), AdditionError> {
         let source = source.borrow();
 
-        // If source is empty there's nothing to add
+        // If source is empty there's nothing to add, other than its time window
         if source.is_empty() {
+            self.widen_time_window(source.start_time, source.end_time);
             return Ok(());
         }
 
@@ -486,12 +712,12 @@ impl<T: Counter> Histogram<T> {
             // If self is empty (all counters are zeroes) we can copy the source histogram with a memory copy.
             self.counts[..].copy_from_slice(&source.counts[..]);
             self.total_count = source.total_count;
+            self.total_count_saturated = source.total_count_saturated;
             self.min_non_zero_value = source.min_non_zero_value;
             self.max_value = source.max_value;
         } else if matching_buckets {
             // Counts arrays are of the same length and meaning,
             // so we can just iterate and add directly:
-            let mut observed_other_total_count: u64 = 0;
             for i in 0..source.distinct_values() {
                 let other_count = source
                     .count_at_index(i)
@@ -500,12 +726,10 @@ impl<T: Counter> Histogram<T> {
                     // indexing is safe: same configuration as `source`, and the index was valid for
                     // `source`.
                     self.counts[i] = self.counts[i].saturating_add(other_count);
-                    observed_other_total_count =
-                        observed_other_total_count.saturating_add(other_count.as_u64());
+                    self.add_to_total_count(other_count);
                 }
             }
 
-            self.total_count = self.total_count.saturating_add(observed_other_total_count);
             let mx = source.max();
             if mx > self.max() {
                 self.update_max(mx);
@@ -514,6 +738,37 @@ impl<T: Counter> Histogram<T> {
             if mn < self.min_nz() {
                 self.update_min(mn);
             }
+        } else if self.sub_bucket_count == source.sub_bucket_count
+            && self.unit_magnitude <= source.unit_magnitude
+        {
+            // Same precision, but source's values are a power of two multiple of ours (e.g. it
+            // tracks microseconds and we track nanoseconds), so each of source's buckets is one of
+            // ours shifted up by the difference in unit magnitude, and indexes are simply offset.
+            // The lower half of source's first bucket is the exception, as it doesn't have a
+            // counterpart in our buckets at that shift.
+            let half_count = self.sub_bucket_half_count as usize;
+            let index_offset =
+                usize::from(source.unit_magnitude - self.unit_magnitude) * half_count;
+            for i in 0..source.distinct_values() {
+                let other_count = source
+                    .count_at_index(i)
+                    .expect("iterating inside source length");
+                if other_count == T::zero() {
+                    continue;
+                }
+
+                if i < half_count {
+                    self.record_n(source.value_for(i), other_count)
+                        .expect("Record must succeed; already resized for max value");
+                } else {
+                    // indexing is safe: we've been resized to hold source's max, and the value at
+                    // this index is no more than that.
+                    let c = &mut self.counts[i + index_offset];
+                    *c = c.saturating_add(other_count);
+                    self.add_to_total_count(other_count);
+                    self.update_min_max(source.value_for(i));
+                }
+            }
         } else {
             // Arrays are not a direct match (or the other could change on the fly in some valid
             // way), so we can't just stream through and add them. Instead, go through the array
@@ -541,16 +796,121 @@ impl<T: Counter> Histogram<T> {
             }
         }
 
-        // TODO:
-        // if source.start_time < self.start_time {
-        //     self.start_time = source.start_time;
-        // }
-        // if source.end_time > self.end_time {
-        //     self.end_time = source.end_time;
-        // }
+        self.widen_time_window(source.start_time, source.end_time);
+        Ok(())
+    }
+
+    /// Add the contents of each of several histograms to this one, as if by calling `add` for each.
+    ///
+    /// If auto-resizing is enabled, this histogram is resized once up front to cover the largest
+    /// value of all the sources, rather than possibly once per source. If it is not and a source
+    /// has values out of range, nothing is added.
+    ///
+    /// Returns an error if values in a source cannot be stored; see `AdditionError`. Sources before
+    /// one that fails for another reason remain added.
+    pub fn add_all<'a, I: IntoIterator<Item = &'a Histogram<T>>>(
+        &mut self,
+        sources: I,
+    ) -> Result<(), AdditionError>
+    where
+        T: 'a,
+    {
+        let sources: Vec<&Histogram<T>> = sources.into_iter().collect();
+
+        let max = sources
+            .iter()
+            .filter(|h| !h.is_empty())
+            .map(|h| h.max())
+            .max();
+        if let Some(max) = max {
+            let top = self.highest_equivalent(self.value_for(self.last_index()));
+            if top < max {
+                if !self.auto_resize {
+                    return Err(AdditionError::OtherAddendValueExceedsRange);
+                }
+                // We're growing the histogram, so new high > old high and is therefore >= 2x low.
+                self.resize(max)
+                    .map_err(|_| AdditionError::ResizeFailedUsizeTypeTooSmall)?;
+            }
+        }
+
+        for source in sources {
+            self.add(source)?;
+        }
         Ok(())
     }
 
+    /// Add the contents of another histogram to this one like `add`, reporting whether this
+    /// histogram had to be resized to accommodate it.
+    ///
+    /// Returns `Ok(true)` if adding `source` caused the counts array to be reallocated (which is
+    /// only possible with auto-resize enabled), and `Ok(false)` otherwise. This is useful for
+    /// monitoring resizes in latency-sensitive aggregation. Errors are the same as for `add`.
+    pub fn add_reporting_resize<B: Borrow<Histogram<T>>>(
+        &mut self,
+        source: B,
+    ) -> Result<bool, AdditionError> {
+        let len = self.counts.len();
+        self.add(source)?;
+        Ok(self.counts.len() != len)
+    }
+
+    /// Add the contents of another histogram to this one, failing instead of saturating if any
+    /// bucket count would overflow `T` or the total count would overflow `u64`.
+    ///
+    /// `add` saturates bucket counts at `T`'s maximum, which silently loses counts when using a
+    /// small counter type, and likewise saturates the total count. This checks every bucket and
+    /// the total before modifying anything, so on error this histogram is left unchanged.
+    ///
+    /// Returns `AdditionError::CountOverflow` if a bucket would overflow,
+    /// `AdditionError::TotalCountOverflow` if the total count would overflow (or either total has
+    /// already saturated), or any other error `add` would return; see `AdditionError`.
+    pub fn add_checked<B: Borrow<Histogram<T>>>(&mut self, source: B) -> Result<(), AdditionError> {
+        let source = source.borrow();
+
+        if source.is_empty() {
+            return self.add(source);
+        }
+        // A saturated total is already inexact, so the sum can't be exact either.
+        if self.total_count_saturated
+            || source.total_count_saturated
+            || self.total_count.checked_add(source.total_count).is_none()
+        {
+            return Err(AdditionError::TotalCountOverflow);
+        }
+
+        // Several of source's buckets may land in the same bucket here, so sum them up as we go.
+        // Indexes only ever increase as source's values do.
+        let mut pending: Option<(usize, T)> = None;
+        for i in 0..source.distinct_values() {
+            let other_count = source
+                .count_at_index(i)
+                .expect("iterating inside source length");
+            if other_count == T::zero() {
+                continue;
+            }
+
+            // Values past our range would land in buckets that are still empty after a resize,
+            // so only the source counts themselves can overflow there.
+            let index = self.index_for(source.value_for(i)).unwrap_or(usize::MAX);
+            let sum = match pending {
+                Some((pending_index, sum)) if pending_index == index => {
+                    sum.checked_add(&other_count)
+                }
+                _ => Some(other_count),
+            }
+            .ok_or(AdditionError::CountOverflow)?;
+            let _ = self
+                .count_at_index(index)
+                .unwrap_or_else(T::zero)
+                .checked_add(&sum)
+                .ok_or(AdditionError::CountOverflow)?;
+            pending = Some((index, sum));
+        }
+
+        self.add(source)
+    }
+
     /// Add the contents of another histogram to this one, while correcting for coordinated
     /// omission.
     ///
@@ -583,7 +943,15 @@ impl<T: Counter> Histogram<T> {
 
     /// Subtract the contents of another histogram from this one.
     ///
-    /// See `SubtractionError` for error conditions.
+    /// If auto-resizing is enabled, this histogram will be resized to cover the other histogram's
+    /// range, even if the subtraction then fails.
+    ///
+    /// The other histogram may have a different configuration. Each of its buckets is then
+    /// subtracted from the bucket its lowest equivalent value falls in here, so the subtraction
+    /// can fail if its buckets are coarser than this histogram's.
+    ///
+    /// See `SubtractionError` for error conditions. Subtracting an empty histogram is a no-op that
+    /// never fails, whatever its range.
     pub fn subtract<B: Borrow<Histogram<T>>>(
         &mut self,
         subtrahend: B,
@@ -598,35 +966,50 @@ impl<T: Counter> Histogram<T> {
         // make sure we can take the values in source
         let top = self.highest_equivalent(self.value_for(self.last_index()));
         if top < self.highest_equivalent(subtrahend.max()) {
-            return Err(SubtractionError::SubtrahendValueExceedsMinuendRange);
+            if !self.auto_resize {
+                return Err(SubtractionError::SubtrahendValueExceedsMinuendRange);
+            }
+            // Like `add`, grow to cover the subtrahend. The new buckets are all zero, so any
+            // counts the subtrahend has there will fail to subtract below.
+            self.resize(subtrahend.max())
+                .map_err(|_| SubtractionError::ResizeFailedUsizeTypeTooSmall)?;
         }
 
+        let matching_buckets = self.bucket_count == subtrahend.bucket_count
+            && self.sub_bucket_count == subtrahend.sub_bucket_count
+            && self.unit_magnitude == subtrahend.unit_magnitude;
+
         let old_min_highest_equiv = self.highest_equivalent(self.min());
         let old_max_lowest_equiv = self.lowest_equivalent(self.max());
 
         // If total_count is at the max value, it may have saturated, so we must restat
         let mut needs_restat = self.total_count == u64::max_value();
 
-        for i in 0..subtrahend.distinct_values() {
+        for i in 0..subtrahend.len_to_max() {
             let other_count = subtrahend
                 .count_at_index(i)
                 .expect("index inside subtrahend len must exist");
             if other_count != T::zero() {
                 let other_value = subtrahend.value_for(i);
                 {
-                    let mut_count = self.mut_at(other_value);
-
-                    if let Some(c) = mut_count {
-                        // TODO Perhaps we should saturating sub here? Or expose some form of
-                        // pluggability so users could choose to error or saturate? Both seem
-                        // useful. It's also sort of inconsistent with overflow, which now
-                        // saturates.
-                        *c = (*c)
-                            .checked_sub(&other_count)
-                            .ok_or(SubtractionError::SubtrahendCountExceedsMinuendCount)?;
+                    // With differing bucket layouts, find where the subtrahend's value lands in
+                    // ours instead of using its index directly.
+                    let index = if matching_buckets {
+                        Some(i)
                     } else {
-                        panic!("Tried to subtract value outside of range: {}", other_value);
-                    }
+                        self.index_for(other_value)
+                    };
+                    let c = index
+                        .and_then(|index| self.counts.get_mut(index))
+                        .ok_or(SubtractionError::SubtrahendValueNotRepresentable)?;
+
+                    // TODO Perhaps we should saturating sub here? Or expose some form of
+                    // pluggability so users could choose to error or saturate? Both seem
+                    // useful. It's also sort of inconsistent with overflow, which now
+                    // saturates.
+                    *c = (*c)
+                        .checked_sub(&other_count)
+                        .ok_or(SubtractionError::SubtrahendCountExceedsMinuendCount)?;
                 }
 
                 // we might have just set the min / max to have zero count.
@@ -637,22 +1020,166 @@ impl<T: Counter> Histogram<T> {
                 if !needs_restat {
                     // if we're not already going to recalculate everything, subtract from
                     // total_count
-                    self.total_count = self
-                        .total_count
-                        .checked_sub(other_count.as_u64())
-                        .expect("total count underflow on subtraction");
+                    match self.total_count.checked_sub(other_count.as_u64()) {
+                        Some(total_count) => self.total_count = total_count,
+                        // Saturated bucket counts mean total_count can't be kept exactly in
+                        // sync with the counts, so recalculate it from scratch instead.
+                        None => needs_restat = true,
+                    }
                 }
             }
         }
 
         if needs_restat {
-            let l = self.distinct_values();
+            let l = self.len_to_max();
             self.restat(l);
         }
 
         Ok(())
     }
 
+    /// Subtract the contents of another histogram from this one like `subtract`, but instead of
+    /// stopping at the first bucket that cannot be subtracted, set each such bucket to zero and
+    /// carry on.
+    ///
+    /// Returns an error listing `(value, available, requested)` for every subtrahend bucket whose
+    /// count exceeded the count available here, in ascending value order. `value` is the lowest
+    /// equivalent value of the bucket in `subtrahend`. Values that have no bucket here, because
+    /// they are out of range and the histogram cannot be resized to fit them or because the
+    /// bucket layouts differ, are reported with an `available` count of zero. All other buckets
+    /// are subtracted as usual either way.
+    pub fn subtract_collecting_errors<B: Borrow<Histogram<T>>>(
+        &mut self,
+        subtrahend: B,
+    ) -> Result<(), Vec<(u64, T, T)>> {
+        let subtrahend = subtrahend.borrow();
+
+        // If the source is empty there's nothing to subtract
+        if subtrahend.is_empty() {
+            return Ok(());
+        }
+
+        let top = self.highest_equivalent(self.value_for(self.last_index()));
+        if top < self.highest_equivalent(subtrahend.max()) && self.auto_resize {
+            // If this fails, the values that don't fit are reported below like any other
+            // unrepresentable value.
+            let _ = self.resize(subtrahend.max());
+        }
+
+        let matching_buckets = self.bucket_count == subtrahend.bucket_count
+            && self.sub_bucket_count == subtrahend.sub_bucket_count
+            && self.unit_magnitude == subtrahend.unit_magnitude;
+
+        let mut conflicts = Vec::new();
+        for i in 0..subtrahend.len_to_max() {
+            let other_count = subtrahend
+                .count_at_index(i)
+                .expect("index inside subtrahend len must exist");
+            if other_count == T::zero() {
+                continue;
+            }
+
+            let other_value = subtrahend.value_for(i);
+            let index = if matching_buckets {
+                Some(i)
+            } else {
+                self.index_for(other_value)
+            };
+            match index.and_then(|index| self.counts.get_mut(index)) {
+                Some(c) => match (*c).checked_sub(&other_count) {
+                    Some(remaining) => *c = remaining,
+                    None => {
+                        conflicts.push((other_value, *c, other_count));
+                        *c = T::zero();
+                    }
+                },
+                None => conflicts.push((other_value, T::zero(), other_count)),
+            }
+        }
+
+        // Counts only went down, so nothing above the old max can be non-zero
+        let l = self.len_to_max();
+        self.restat(l);
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Return a new histogram with the contents of `subtrahend` subtracted from this one, leaving
+    /// this histogram unchanged.
+    ///
+    /// This is equivalent to cloning this histogram and calling `subtract` on the clone, and fails
+    /// in the same cases.
+    pub fn subtracted<B: Borrow<Histogram<T>>>(
+        &self,
+        subtrahend: B,
+    ) -> Result<Histogram<T>, SubtractionError> {
+        let mut difference = self.clone();
+        difference.subtract(subtrahend)?;
+        Ok(difference)
+    }
+
+    /// Compare the counts of this histogram with those of another, bucket by bucket.
+    ///
+    /// Returns `(value, self_count - other_count)` for every bucket that has a non-zero count in
+    /// either histogram, in ascending value order. `value` is the lowest equivalent value of the
+    /// bucket in this histogram. A positive difference means this histogram has more counts in
+    /// that bucket; a negative one means `other` does. Counts that do not fit in an `i64`
+    /// saturate, as does the difference.
+    ///
+    /// `other` may have a different configuration, in which case its values are mapped to the
+    /// corresponding buckets in this histogram just like `add` would. If `other` contains values
+    /// that do not fit in this histogram's range, `AdditionError::OtherAddendValueExceedsRange`
+    /// is returned.
+    pub fn count_diff<B: Borrow<Histogram<T>>>(
+        &self,
+        other: B,
+    ) -> Result<Vec<(u64, i64)>, AdditionError> {
+        let other = other.borrow();
+
+        let top = self.highest_equivalent(self.value_for(self.last_index()));
+        if top < other.max() {
+            return Err(AdditionError::OtherAddendValueExceedsRange);
+        }
+
+        let to_i64 = |count: T| count.to_i64().unwrap_or(i64::MAX);
+
+        // None for buckets that are empty in both histograms
+        let mut diffs: Vec<Option<i64>> = self
+            .counts
+            .iter()
+            .map(|&c| {
+                if c == T::zero() {
+                    None
+                } else {
+                    Some(to_i64(c))
+                }
+            })
+            .collect();
+
+        for i in 0..other.distinct_values() {
+            let other_count = other
+                .count_at_index(i)
+                .expect("index inside other len must exist");
+            if other_count != T::zero() {
+                let index = self
+                    .index_for(other.value_for(i))
+                    .expect("value inside other max must have an index");
+                let diff = &mut diffs[index];
+                *diff = Some(diff.unwrap_or(0).saturating_sub(to_i64(other_count)));
+            }
+        }
+
+        Ok(diffs
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, diff)| diff.map(|d| (self.value_for(i), d)))
+            .collect())
+    }
+
     // ********************************************************************************************
     // Setters and resetters.
     // ********************************************************************************************
@@ -663,6 +1190,22 @@ impl<T: Counter> Histogram<T> {
             *c = T::zero();
         }
         self.total_count = 0;
+        self.total_count_saturated = false;
+    }
+
+    /// Multiply every count in this histogram by `factor`, e.g. when each recorded sample
+    /// represents `factor` events.
+    ///
+    /// Counts that would exceed what `T` can represent saturate at its maximum value, so take
+    /// care with small counter types like `u8` or `u16`. The total count is recalculated
+    /// afterwards.
+    pub fn scale_counts(&mut self, factor: u64) {
+        for c in &mut self.counts {
+            *c = Self::saturating_mul_count(*c, factor);
+        }
+
+        let l = self.len_to_max();
+        self.restat(l);
     }
 
     /// Reset the contents and statistics of this histogram, preserving only its configuration.
@@ -672,15 +1215,61 @@ impl<T: Counter> Histogram<T> {
         self.reset_max(ORIGINAL_MAX);
         self.reset_min(ORIGINAL_MIN);
         // self.normalizing_index_offset = 0;
-        // self.start_time = time::Instant::now();
-        // self.end_time = time::Instant::now();
+        self.start_time = None;
+        self.end_time = None;
+        self.min_value_time = None;
+        self.max_value_time = None;
+        self.clamped_low_count = 0;
+        self.clamped_high_count = 0;
         // self.tag = String::new();
     }
 
+    /// Reset the contents and statistics of this histogram like `reset`, and also shrink it back
+    /// to its originally configured highest trackable value, freeing the memory used by any
+    /// buckets added by auto-resizing since.
+    ///
+    /// This is useful for histograms that are reused after occasionally recording huge values.
+    pub fn reset_and_shrink(&mut self) {
+        self.reset();
+
+        if self.highest_trackable_value > self.initial_highest_trackable_value {
+            self.resize(self.initial_highest_trackable_value)
+                .expect("Originally configured size must be representable");
+            self.counts.shrink_to_fit();
+        }
+    }
+
     /// Control whether or not the histogram can auto-resize and auto-adjust it's highest trackable
     /// value as high-valued samples are recorded.
+    ///
+    /// Enabling auto-resize is the same as `on_out_of_range(OutOfRangePolicy::Resize)`, so it
+    /// clears `OutOfRangePolicy::Clamp` if that was set. Disabling it turns `Resize` into
+    /// `OutOfRangePolicy::Error`, but leaves `Clamp` in place.
     pub fn auto(&mut self, enabled: bool) {
         self.auto_resize = enabled;
+        if enabled {
+            self.clamp_out_of_range = false;
+        }
+    }
+
+    /// Control how `record`, `record_n`, `+=` and the coordinated omission correcting
+    /// `record_correct` and `record_n_correct` handle values outside the histogram's range.
+    ///
+    /// This centralizes the choice between, say, `record` and `saturating_record` in the
+    /// histogram's configuration. Methods with explicit behavior, like `saturating_record`, are
+    /// unaffected. See `OutOfRangePolicy` for the options.
+    ///
+    /// ```
+    /// use hdrhistogram::{Histogram, OutOfRangePolicy};
+    /// let mut hist = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    /// hist.on_out_of_range(OutOfRangePolicy::Clamp);
+    ///
+    /// hist += 1_000_000;
+    /// assert_eq!(hist.highest_equivalent(1000), hist.max());
+    /// ```
+    pub fn on_out_of_range(&mut self, policy: OutOfRangePolicy) {
+        self.auto_resize = policy == OutOfRangePolicy::Resize;
+        self.clamp_out_of_range = policy == OutOfRangePolicy::Clamp;
     }
 
     // ********************************************************************************************
@@ -702,6 +1291,25 @@ impl<T: Counter> Histogram<T> {
         h
     }
 
+    /// Construct an auto-resizing `Histogram` like [`new`], but already sized to track values up
+    /// to `expected_max`. This avoids the reallocations `new` would go through as the first large
+    /// samples arrive, while still allowing the histogram to grow beyond `expected_max` later.
+    ///
+    /// See [`new_with_bounds`] for info on `sigfig`.
+    ///
+    /// [`new`]: #method.new
+    /// [`new_with_bounds`]: #method.new_with_bounds
+    pub fn new_auto_with_capacity(
+        sigfig: u8,
+        expected_max: u64,
+    ) -> Result<Histogram<T>, CreationError> {
+        let mut h = Self::new_with_bounds(1, cmp::max(expected_max, 2), sigfig);
+        if let Ok(ref mut h) = h {
+            h.auto_resize = true;
+        }
+        h
+    }
+
     /// Construct a `Histogram` given a known maximum value to be tracked, and a number of
     /// significant decimal digits. The histogram will be constructed to implicitly track
     /// (distinguish from 0) values as low as 1. Auto-resizing will be disabled.
@@ -734,6 +1342,58 @@ impl<T: Counter> Histogram<T> {
     ///
     /// Returns an error if the provided parameters are invalid; see `CreationError`.
     pub fn new_with_bounds(low: u64, high: u64, sigfig: u8) -> Result<Histogram<T>, CreationError> {
+        Self::new_with_bounds_inner(low, high, sigfig, None)
+    }
+
+    /// Construct a `Histogram` with known upper and lower bounds for recorded sample values, and
+    /// enough precision to keep the relative error at or below `max_relative_error`.
+    ///
+    /// This picks the smallest number of significant figures whose resolution guarantee is good
+    /// enough, e.g. 2 for an error of 0.01 (1%) or 3 for 0.001, and otherwise behaves like
+    /// [`new_with_bounds`]. As with `relative_error_at`, the guarantee holds for values of at least
+    /// `low * 10^sigfig`; smaller values are tracked exactly in units of `low`.
+    ///
+    /// Returns `CreationError::SigFigExceedsMax` if `max_relative_error` is below 0.00001 (or is
+    /// not a positive number), as that would take more than 5 significant figures.
+    ///
+    /// [`new_with_bounds`]: #method.new_with_bounds
+    pub fn new_with_error(
+        low: u64,
+        high: u64,
+        max_relative_error: f64,
+    ) -> Result<Histogram<T>, CreationError> {
+        let sigfig = (0..=5_u8)
+            .find(|&sigfig| max_relative_error * 10_f64.powi(i32::from(sigfig)) >= 1.0)
+            .ok_or(CreationError::SigFigExceedsMax)?;
+        Self::new_with_bounds(low, high, sigfig)
+    }
+
+    /// Construct a `Histogram` with known upper and lower bounds for recorded sample values, as
+    /// long as its counts array would take no more than `max_bytes` of memory.
+    ///
+    /// This is useful when the configuration comes from untrusted input. The size is checked
+    /// before anything is allocated, and `CreationError::ExceedsMemoryBudget` is returned if it is
+    /// too large. Note that the histogram may still grow past `max_bytes` if auto-resizing is
+    /// enabled later.
+    ///
+    /// See [`new_with_bounds`] for info on `low`, `high`, and `sigfig`.
+    ///
+    /// [`new_with_bounds`]: #method.new_with_bounds
+    pub fn new_with_bounds_capped(
+        low: u64,
+        high: u64,
+        sigfig: u8,
+        max_bytes: usize,
+    ) -> Result<Histogram<T>, CreationError> {
+        Self::new_with_bounds_inner(low, high, sigfig, Some(max_bytes))
+    }
+
+    fn new_with_bounds_inner(
+        low: u64,
+        high: u64,
+        sigfig: u8,
+        max_bytes: Option<usize>,
+    ) -> Result<Histogram<T>, CreationError> {
         // Verify argument validity
         if low < 1 {
             return Err(CreationError::LowIsZero);
@@ -743,7 +1403,7 @@ impl<T: Counter> Histogram<T> {
             return Err(CreationError::LowExceedsMax);
         }
         if high < 2 * low {
-            return Err(CreationError::HighLessThanTwiceLow);
+            return Err(CreationError::HighLessThanTwiceLow { min_high: 2 * low });
         }
         if sigfig > 5 {
             return Err(CreationError::SigFigExceedsMax);
@@ -787,8 +1447,10 @@ impl<T: Counter> Histogram<T> {
 
         let mut h = Histogram {
             auto_resize: false,
+            clamp_out_of_range: false,
 
             highest_trackable_value: high,
+            initial_highest_trackable_value: high,
             lowest_discernible_value: low,
             significant_value_digits: sigfig,
 
@@ -811,10 +1473,34 @@ impl<T: Counter> Histogram<T> {
             min_non_zero_value: ORIGINAL_MIN,
 
             total_count: 0,
+            total_count_saturated: false,
             // set by alloc() below
             counts: Vec::new(),
+
+            start_time: None,
+            end_time: None,
+            min_value_time: None,
+            max_value_time: None,
+
+            clamped_low_count: 0,
+            clamped_high_count: 0,
+
+            #[cfg(feature = "metrics")]
+            record_count: 0,
+            #[cfg(feature = "metrics")]
+            resize_count: 0,
         };
 
+        if let Some(max_bytes) = max_bytes {
+            let bytes = h
+                .num_bins(h.buckets_to_cover(high))
+                .to_usize()
+                .and_then(|len| len.checked_mul(std::mem::size_of::<T>()));
+            if bytes.filter(|&bytes| bytes <= max_bytes).is_none() {
+                return Err(CreationError::ExceedsMemoryBudget);
+            }
+        }
+
         // Already checked that high >= 2*low
         h.resize(high)
             .map_err(|_| CreationError::UsizeTypeTooSmall)?;
@@ -823,6 +1509,20 @@ impl<T: Counter> Histogram<T> {
 
     /// Construct a `Histogram` with the same range settings as a given source histogram,
     /// duplicating the source's start/end timestamps (but NOT its contents).
+    ///
+    /// The source may use a different counter type, so this can be used to start e.g. an empty
+    /// `u32` accumulator shaped like a `u64` histogram. The new histogram also has the source's
+    /// auto-resize setting and current size.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut source = Histogram::<u64>::new_with_bounds(10, 1_000_000, 3).unwrap();
+    /// source += 1000;
+    ///
+    /// let h = Histogram::<u32>::new_from(&source);
+    /// assert!(h.is_empty());
+    /// assert_eq!((10, 1_000_000, 3), (h.low(), h.high(), h.sigfig()));
+    /// ```
     pub fn new_from<F: Counter>(source: &Histogram<F>) -> Histogram<T> {
         let mut h = Self::new_with_bounds(
             source.lowest_discernible_value,
@@ -831,25 +1531,164 @@ impl<T: Counter> Histogram<T> {
         )
         .expect("Using another histogram's parameters failed");
 
-        // h.start_time = source.start_time;
-        // h.end_time = source.end_time;
+        h.start_time = source.start_time;
+        h.end_time = source.end_time;
         h.auto_resize = source.auto_resize;
+        h.clamp_out_of_range = source.clamp_out_of_range;
+        h.initial_highest_trackable_value = source.initial_highest_trackable_value;
         h.counts.resize(source.distinct_values(), T::zero());
         h
     }
 
+    /// Construct a `Histogram` from a snapshot of its configuration and counts, such as one
+    /// exported by another HdrHistogram implementation.
+    ///
+    /// `counts` holds the count at each index of a histogram created with the given `low`,
+    /// `high`, and `sigfig` (see [`new_with_bounds`]). Trailing zero counts may be omitted. The
+    /// counts may be stored with a wider type than `T`: if any count does not fit in `T`,
+    /// `SnapshotError::UnsuitableCounterType` is returned rather than silently saturating.
+    ///
+    /// [`new_with_bounds`]: #method.new_with_bounds
+    pub fn new_from_snapshot_counts(
+        low: u64,
+        high: u64,
+        sigfig: u8,
+        counts: &[u64],
+    ) -> Result<Histogram<T>, SnapshotError> {
+        let mut h =
+            Self::new_with_bounds(low, high, sigfig).map_err(SnapshotError::CreationError)?;
+        if counts.len() > h.counts.len() {
+            return Err(SnapshotError::CountsArrayTooLong);
+        }
+
+        for (c, &count) in h.counts.iter_mut().zip(counts) {
+            *c = T::from_u64(count).ok_or(SnapshotError::UnsuitableCounterType)?;
+        }
+
+        h.restat(counts.len());
+        Ok(h)
+    }
+
+    /// Export the counts at each index of this histogram, for use with
+    /// [`new_from_snapshot_counts`] along with `low()`, `high()`, and `sigfig()`.
+    ///
+    /// Trailing zero counts are omitted. Counts that do not fit in a `u64` (only possible with
+    /// `u128` counters) saturate.
+    ///
+    /// [`new_from_snapshot_counts`]: #method.new_from_snapshot_counts
+    pub fn to_snapshot_counts(&self) -> Vec<u64> {
+        let len = if self.is_empty() {
+            0
+        } else {
+            self.index_for_or_last(self.max()) + 1
+        };
+        self.counts[..len].iter().map(Counter::as_u64).collect()
+    }
+
+    /// Create a copy of this histogram, including its contents.
+    ///
+    /// This is like `clone`, except that it returns an error instead of aborting if the counts
+    /// array cannot be allocated.
+    pub fn try_clone(&self) -> Result<Histogram<T>, TryReserveError> {
+        let mut counts = Vec::new();
+        counts.try_reserve_exact(self.counts.len())?;
+        counts.extend_from_slice(&self.counts);
+
+        Ok(Histogram { counts, ..*self })
+    }
+
     // ********************************************************************************************
     // Recording samples.
     // ********************************************************************************************
 
+    /// Returns true if `record(value)` would succeed, without recording anything.
+    ///
+    /// With auto-resize disabled, this is whether `value` fits in the histogram's current range.
+    /// With auto-resize enabled, any value whose index can be represented in `usize` can be
+    /// recorded, although the histogram may then have to be resized for it. With
+    /// `OutOfRangePolicy::Clamp`, any value can be recorded, although it may be clamped.
+    pub fn can_record(&self, value: u64) -> bool {
+        if self.clamp_out_of_range {
+            return true;
+        }
+        match self.index_for(value) {
+            Some(index) => self.auto_resize || index < self.counts.len(),
+            None => false,
+        }
+    }
+
     /// Record `value` in the histogram.
     ///
     /// Returns an error if `value` exceeds the highest trackable value and auto-resize is
-    /// disabled.
+    /// disabled, unless the histogram clamps out-of-range values; see `on_out_of_range`.
+    #[inline]
     pub fn record(&mut self, value: u64) -> Result<(), RecordError> {
+        // Fast path for the common case of a value that fits without resizing. This is the same as
+        // `record_n_inner`, but skips the resize handling and the conversion of the count. Values
+        // below `low()` go through `record_n` when clamping so that they are counted as clamped.
+        if !(self.clamp_out_of_range && value < self.lowest_discernible_value) {
+            if let Some(c) = self.mut_at(value) {
+                *c = c.saturating_add(T::one());
+                self.update_min_max(value);
+                match self.total_count.checked_add(1) {
+                    Some(total_count) => self.total_count = total_count,
+                    None => self.total_count_saturated = true,
+                }
+                #[cfg(feature = "metrics")]
+                {
+                    self.record_count += 1;
+                }
+                return Ok(());
+            }
+        }
+
         self.record_n(value, T::one())
     }
 
+    /// Record `value` in the histogram like `record`, returning the index of the bucket it was
+    /// recorded in.
+    ///
+    /// This is useful for keeping external per-bucket metadata alongside the histogram. If the
+    /// histogram was resized for the value, the index is in the resized counts array. If the value
+    /// was clamped to the highest trackable value (see `on_out_of_range`), it is the index of that
+    /// value. Values below the lowest discernible value are recorded in the first bucket.
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`.
+    pub fn record_returning_index(&mut self, value: u64) -> Result<usize, RecordError> {
+        self.record(value)?;
+        // A value that still doesn't fit was clamped to the highest trackable value. Values
+        // below the lowest discernible value always have an index in the first bucket.
+        Ok(self
+            .index_for(value)
+            .filter(|&index| index < self.counts.len())
+            .or_else(|| self.index_for(self.highest_trackable_value))
+            .expect("highest trackable value is always representable"))
+    }
+
+    /// Record a non-zero `value` in the histogram.
+    ///
+    /// This is the same as `record`, but makes it clear at the type level that the value is
+    /// positive, so that a zero (or a negative number cast to `u64`) can't slip in by accident.
+    ///
+    /// Returns an error if `value` exceeds the highest trackable value and auto-resize is
+    /// disabled.
+    pub fn record_nonzero(&mut self, value: NonZeroU64) -> Result<(), RecordError> {
+        self.record(value.get())
+    }
+
+    /// Record `value` in the histogram, reporting whether the histogram had to be resized to
+    /// accommodate it.
+    ///
+    /// Returns `Ok(true)` if recording the value caused the counts array to be reallocated, and
+    /// `Ok(false)` otherwise. This is useful for detecting allocations on latency-sensitive
+    /// recording paths.
+    ///
+    /// Returns an error if `value` exceeds the highest trackable value and auto-resize is
+    /// disabled.
+    pub fn record_checked(&mut self, value: u64) -> Result<bool, RecordError> {
+        self.record_n_inner(value, T::one(), false)
+    }
+
     /// Record `value` in the histogram, clamped to the range of the histogram.
     ///
     /// This method cannot fail, as any values that are too small or too large to be tracked will
@@ -860,13 +1699,115 @@ impl<T: Counter> Histogram<T> {
         self.saturating_record_n(value, T::one())
     }
 
+    /// Record `value` in the histogram, clamped to the range of the histogram, and report whether
+    /// it had to be clamped.
+    ///
+    /// Returns `true` if `value` was above the highest trackable value (and so was recorded as
+    /// that value instead), or if it was non-zero but below `low()` (and so is indistinguishable
+    /// from the values in the lowest bucket). Returns `false` if `value` was recorded as-is. As
+    /// with `saturating_record`, the histogram is never resized.
+    pub fn saturating_record_checked(&mut self, value: u64) -> bool {
+        let below_low = value != 0 && value < self.lowest_discernible_value;
+        let clamped = self.record_n_inner(value, T::one(), true).unwrap();
+        clamped || below_low
+    }
+
+    /// Record `value` in the histogram, and widen the histogram's time window to include `when`.
+    ///
+    /// This keeps track of the time window covered by the samples (see `start_time` and
+    /// `end_time`), e.g. for interval logging. Samples may be recorded in any order.
+    ///
+    /// If `value` is a new minimum or maximum, `when` is also kept as its time (see
+    /// `min_value_time` and `max_value_time`).
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`. The time window is left
+    /// unchanged in that case.
+    pub fn record_at(&mut self, value: u64, when: time::SystemTime) -> Result<(), RecordError> {
+        let was_empty = self.is_empty();
+        let old_min = self.min();
+        let old_max = self.max();
+
+        self.record(value)?;
+        self.widen_time_window(Some(when), Some(when));
+        if was_empty || self.min() < old_min {
+            self.min_value_time = Some(when);
+        }
+        if was_empty || self.max() > old_max {
+            self.max_value_time = Some(when);
+        }
+        Ok(())
+    }
+
+    /// Record `value` in the histogram, then get the quantile of samples at or below it (see
+    /// `quantile_below`), including the sample just recorded.
+    ///
+    /// This is handy for adaptive systems that react to where each new sample falls in the
+    /// distribution so far. Note that unlike recording, finding the quantile is O(n) in the number
+    /// of buckets, so this is not meant for the hottest loops.
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`.
+    pub fn record_then_quantile(&mut self, value: u64) -> Result<f64, RecordError> {
+        self.record(value)?;
+        Ok(self.quantile_below(value))
+    }
+
+    /// Record a signed `value` in the histogram, clamping negative values to `low()`.
+    ///
+    /// This is convenient when recording signed measurements such as deltas, where casting a
+    /// negative value with `as u64` would silently record a huge value instead.
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`.
+    pub fn record_i64(&mut self, value: i64) -> Result<(), RecordError> {
+        let value = if value < 0 { self.low() } else { value as u64 };
+        self.record(value)
+    }
+
     /// Record multiple samples for a value in the histogram, adding to the value's current count.
     ///
     /// `count` is the number of occurrences of this value to record.
     ///
     /// Returns an error if `value` cannot be recorded; see `RecordError`.
     pub fn record_n(&mut self, value: u64, count: T) -> Result<(), RecordError> {
-        self.record_n_inner(value, count, false)
+        self.record_n_inner(value, count, self.clamp_out_of_range)
+            .map(|_| ())
+    }
+
+    /// Record multiple samples for a value in the histogram, unless that would make the total
+    /// count exceed `u64::max_value()`.
+    ///
+    /// `record_n` saturates the total count, after which `len()` and quantile calculations are no
+    /// longer accurate (see `total_count_saturated`). This instead returns
+    /// `RecordError::TotalCountOverflow` without recording anything, so callers can stop before the
+    /// total becomes unreliable. Individual bucket counts still saturate as with `record_n`.
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`.
+    pub fn record_n_total_checked(&mut self, value: u64, count: T) -> Result<(), RecordError> {
+        let _ = count
+            .to_u64()
+            .and_then(|count| self.total_count.checked_add(count))
+            .ok_or(RecordError::TotalCountOverflow)?;
+        self.record_n(value, count)
+    }
+
+    /// Record `value` with a fractional weight, e.g. the effective count of a sample taken with a
+    /// non-integer sampling weight.
+    ///
+    /// Since bucket counts are integers of type `T`, `weight` is rounded to the nearest integer
+    /// (halves round up), which is then recorded like `record_n`. A weight that rounds to 0 records
+    /// nothing, and one that is too large for `T` saturates at `T::max_value()`.
+    ///
+    /// Returns `RecordError::InvalidWeight` if `weight` is negative, infinite, or NaN, and
+    /// otherwise errors if `value` cannot be recorded; see `RecordError`.
+    pub fn record_weighted(&mut self, value: u64, weight: f64) -> Result<(), RecordError> {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(RecordError::InvalidWeight);
+        }
+
+        let count = weight.round();
+        if count == 0.0 {
+            return Ok(());
+        }
+        self.record_n(value, T::from_f64(count).unwrap_or_else(T::max_value))
     }
 
     /// Record multiple samples for a value in the histogram, each one clamped to the histogram's
@@ -877,12 +1818,144 @@ impl<T: Counter> Histogram<T> {
     /// This method cannot fail, as values that are too small or too large to be recorded will
     /// automatically be clamed to be in range. Be aware that this *will* hide extreme outliers
     /// from the resulting histogram without warning. Since the values are clamped, the histogram
-    /// will also not be resized to accomodate the value, even if auto-resize is enabled.
+    /// will also not be resized to accomodate the value, even if auto-resize is enabled. The
+    /// number of clamped samples is tallied in `clamped_low_count` and `clamped_high_count`.
     pub fn saturating_record_n(&mut self, value: u64, count: T) {
-        self.record_n_inner(value, count, true).unwrap()
+        let _ = self.record_n_inner(value, count, true).unwrap();
+    }
+
+    /// Record `count` samples for `value`, where each sample stands for `batch_size` events that
+    /// all had that value, e.g. a single ack covering a batch of messages.
+    ///
+    /// This records `count * batch_size` occurrences of `value`. Unlike the coordinated omission
+    /// correction of `record_n_correct`, which backfills *smaller* values for a sample that took
+    /// longer than expected, this only adds more of the *same* value.
+    ///
+    /// Like `saturating_record_n`, this cannot fail: `value` is clamped to the histogram's range,
+    /// and the total count saturates at `T::max_value()` if it doesn't fit in `T`.
+    pub fn record_n_coalesced(&mut self, value: u64, count: T, batch_size: u64) {
+        let total = Self::saturating_mul_count(count, batch_size);
+        self.saturating_record_n(value, total)
     }
 
-    fn record_n_inner(&mut self, mut value: u64, count: T, clamp: bool) -> Result<(), RecordError> {
+    /// Record multiple samples for each of several values, as `(value, count)` pairs.
+    ///
+    /// This is handy for loading histogram data from other sources, and calls `record_n` for each
+    /// pair. Recording stops at the first pair that cannot be recorded, so pairs before it remain
+    /// recorded.
+    ///
+    /// Returns an error if a value cannot be recorded; see `RecordError`.
+    pub fn record_pairs<I: IntoIterator<Item = (u64, T)>>(
+        &mut self,
+        pairs: I,
+    ) -> Result<(), RecordError> {
+        for (value, count) in pairs {
+            self.record_n(value, count)?;
+        }
+        Ok(())
+    }
+
+    /// Record multiple samples for each of several values, as `(value, count)` pairs, faster than
+    /// `record_pairs` for large imports.
+    ///
+    /// This finds the range of the values up front, resizes at most once to fit the largest one,
+    /// and then adds the counts directly to their buckets, updating the min and max only once at
+    /// the end. The result is the same as calling `record_n` for each pair, including clamping
+    /// out-of-range values under `OutOfRangePolicy::Clamp`.
+    ///
+    /// Unlike `record_pairs`, nothing is recorded if any value cannot be recorded.
+    ///
+    /// Returns an error if a value cannot be recorded; see `RecordError`.
+    pub fn record_pairs_bulk(&mut self, pairs: &[(u64, T)]) -> Result<(), RecordError> {
+        // values that are unit-equivalent to 0 don't affect the min, so skip them here
+        let unit_magnitude_mask = self.unit_magnitude_mask;
+        let (min_nz, max) = pairs
+            .iter()
+            .fold((u64::MAX, 0), |(min_nz, max), &(value, _)| {
+                let min_nz = if value > unit_magnitude_mask && value < min_nz {
+                    value
+                } else {
+                    min_nz
+                };
+                (min_nz, cmp::max(max, value))
+            });
+
+        // when clamping, values that don't fit are recorded as the highest trackable value instead
+        let mut max = max;
+        if self.mut_at(max).is_none() {
+            if self.clamp_out_of_range {
+                max = self.highest_trackable_value;
+            } else if !self.auto_resize {
+                return Err(RecordError::ValueOutOfRangeResizeDisabled);
+            } else {
+                // We're growing the histogram, so new high > old high and is therefore >= 2x low.
+                self.resize(max)
+                    .map_err(|_| RecordError::ResizeFailedUsizeTypeTooSmall)?;
+                self.highest_trackable_value =
+                    self.highest_equivalent(self.value_for(self.last_index()));
+            }
+        }
+
+        for &(value, count) in pairs {
+            if self.clamp_out_of_range && value != 0 && value < self.lowest_discernible_value {
+                self.clamped_low_count = self.clamped_low_count.saturating_add(count.as_u64());
+            }
+            let value = if value > max {
+                self.clamped_high_count = self.clamped_high_count.saturating_add(count.as_u64());
+                max
+            } else {
+                value
+            };
+            let index = self
+                .index_for(value)
+                .expect("Index must exist; already resized for max value");
+            // indexing is safe: no value is larger than max, which fits
+            let c = &mut self.counts[index];
+            *c = c.saturating_add(count);
+            self.add_to_total_count(count);
+        }
+
+        if !pairs.is_empty() {
+            self.update_min_max(max);
+            // min_nz is left at u64::MAX if every value is unit-equivalent to 0
+            if min_nz != u64::MAX {
+                self.update_min_max(cmp::min(min_nz, max));
+            }
+        }
+        #[cfg(feature = "metrics")]
+        {
+            self.record_count += pairs.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Start a `RecordBatch` of `(value, count)` entries to be recorded into a histogram later, all
+    /// at once.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new(3).unwrap();
+    ///
+    /// let mut batch = Histogram::<u64>::batch();
+    /// batch.add(10, 2);
+    /// batch.add(1_000_000, 1);
+    /// batch.commit(&mut hist).unwrap();
+    ///
+    /// assert_eq!(3, hist.len());
+    /// assert_eq!(2, hist.count_at(10));
+    /// ```
+    pub fn batch() -> RecordBatch<T> {
+        RecordBatch::new()
+    }
+
+    /// Returns whether `value` did not fit in the histogram as it was, and so had to be either
+    /// clamped (if `clamp` is set) or have the histogram resized to accommodate it.
+    fn record_n_inner(
+        &mut self,
+        mut value: u64,
+        count: T,
+        clamp: bool,
+    ) -> Result<bool, RecordError> {
         let recorded_without_resize = if let Some(c) = self.mut_at(value) {
             *c = (*c).saturating_add(count);
             true
@@ -890,9 +1963,17 @@ impl<T: Counter> Histogram<T> {
             false
         };
 
+        if clamp && value != 0 && value < self.lowest_discernible_value {
+            self.clamped_low_count = self.clamped_low_count.saturating_add(count.as_u64());
+        }
+
+        let mut adjusted = false;
         if !recorded_without_resize {
+            adjusted = true;
             if clamp {
                 value = if value > self.highest_trackable_value {
+                    self.clamped_high_count =
+                        self.clamped_high_count.saturating_add(count.as_u64());
                     self.highest_trackable_value
                 } else {
                     // must be smaller than the lowest_discernible_value, since self.mut_at(value)
@@ -924,8 +2005,12 @@ impl<T: Counter> Histogram<T> {
         }
 
         self.update_min_max(value);
-        self.total_count = self.total_count.saturating_add(count.as_u64());
-        Ok(())
+        self.add_to_total_count(count);
+        #[cfg(feature = "metrics")]
+        {
+            self.record_count += 1;
+        }
+        Ok(adjusted)
     }
 
     /// Record a value in the histogram while correcting for coordinated omission.
@@ -935,6 +2020,19 @@ impl<T: Counter> Histogram<T> {
         self.record_n_correct(value, T::one(), interval)
     }
 
+    /// Record a value in the histogram while correcting for coordinated omission.
+    ///
+    /// This is an alias for `record_correct`, named after the Java implementation's
+    /// `recordValueWithExpectedInterval`. Likewise, Java's `recordValueWithCount` corresponds to
+    /// `record_n`, and `addWhileCorrectingForCoordinatedOmission` to `add_correct`.
+    pub fn record_with_interval(
+        &mut self,
+        value: u64,
+        expected_interval: u64,
+    ) -> Result<(), RecordError> {
+        self.record_correct(value, expected_interval)
+    }
+
     /// Record multiple values in the histogram while correcting for coordinated omission.
     ///
     /// To compensate for the loss of sampled values when a recorded value is larger than the
@@ -954,21 +2052,61 @@ impl<T: Counter> Histogram<T> {
         count: T,
         interval: u64,
     ) -> Result<(), RecordError> {
-        self.record_n(value, count)?;
+        self.record_n_correct_counted(value, count, interval, self.clamp_out_of_range)
+            .map(|_| ())
+    }
+
+    /// Record multiple values in the histogram, correcting for coordinated omission with the given
+    /// strategy.
+    ///
+    /// With `CorrectionStrategy::Linear` this is the same as `record_n_correct`, and with
+    /// `CorrectionStrategy::None` it is the same as `record_n`. This is useful when the choice of
+    /// correction is configuration, e.g. to record uncorrected data and correct it later with
+    /// `clone_correct` or `add_correct` instead.
+    ///
+    /// Returns an error if `value` exceeds the highest trackable value and auto-resize is
+    /// disabled.
+    pub fn record_n_correct_with(
+        &mut self,
+        value: u64,
+        count: T,
+        interval: u64,
+        strategy: CorrectionStrategy,
+    ) -> Result<(), RecordError> {
+        match strategy {
+            CorrectionStrategy::Linear => self.record_n_correct(value, count, interval),
+            CorrectionStrategy::None => self.record_n(value, count),
+        }
+    }
+
+    /// Returns the number of auto-generated samples recorded. If `clamp` is set, values that don't
+    /// fit are clamped like `saturating_record_n` does, so this never errors.
+    fn record_n_correct_counted(
+        &mut self,
+        value: u64,
+        count: T,
+        interval: u64,
+        clamp: bool,
+    ) -> Result<u64, RecordError> {
+        let _ = self.record_n_inner(value, count, clamp)?;
         if interval == 0 {
-            return Ok(());
+            return Ok(0);
         }
 
+        let mut synthetic: u64 = 0;
         if value > interval {
             // only enter loop when calculations will stay non-negative
+            // This matches the Java implementation: backfill down to and including `interval`,
+            // so the first backfill happens once `value >= 2 * interval`.
             let mut missing_value = value - interval;
             while missing_value >= interval {
-                self.record_n_inner(missing_value, count, false)?;
+                let _ = self.record_n_inner(missing_value, count, clamp)?;
+                synthetic = synthetic.saturating_add(count.as_u64());
                 missing_value -= interval;
             }
         }
 
-        Ok(())
+        Ok(synthetic)
     }
 
     // ********************************************************************************************
@@ -984,7 +2122,7 @@ impl<T: Counter> Histogram<T> {
     /// `halving_period` values have been emitted, the quantile  step size is halved, and the
     /// iteration continues.
     ///
-    /// `ticks_per_half_distance` must be at least 1.
+    /// `ticks_per_half_distance` should be at least 1; 0 is treated as 1.
     ///
     /// The iterator yields an `iterators::IterationValue` struct.
     ///
@@ -1017,27 +2155,37 @@ impl<T: Counter> Histogram<T> {
     /// // step size = 50
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(hist.value_at_quantile(0.5), 0.5, 0.5, 1, 5000 - 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         hist.value_at_quantile(0.5), 0.5, 0.5, 1, 5000 - 1, 12_497_500
+    ///     ))
     /// );
     /// // step size = 25
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(hist.value_at_quantile(0.75), 0.75, 0.75, 1, 2500))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         hist.value_at_quantile(0.75), 0.75, 0.75, 1, 2500, 15_623_750
+    ///     ))
     /// );
     /// // step size = 12.5
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(hist.value_at_quantile(0.875), 0.875, 0.875, 1, 1250))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         hist.value_at_quantile(0.875), 0.875, 0.875, 1, 1250, 10_155_625
+    ///     ))
     /// );
     /// // step size = 6.25
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(hist.value_at_quantile(0.9375), 0.9375, 0.9375, 1, 625))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         hist.value_at_quantile(0.9375), 0.9375, 0.9375, 1, 625, 5_663_750
+    ///     ))
     /// );
     /// // step size = 3.125
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(hist.value_at_quantile(0.9688), 0.9688, 0.96875, 1, 313))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         hist.value_at_quantile(0.9688), 0.9688, 0.96875, 1, 313, 2_983_203
+    ///     ))
     /// );
     /// // etc...
     /// ```
@@ -1049,11 +2197,53 @@ impl<T: Counter> Histogram<T> {
         iterators::quantile::Iter::new(self, ticks_per_half_distance)
     }
 
+    /// Iterate through histogram values by quantile levels, restricted to the quantile range
+    /// `[start_quantile, end_quantile]`.
+    ///
+    /// This behaves like `iter_quantiles`, except that iteration starts at `start_quantile`
+    /// rather than 0.0, and stops once `end_quantile` has been emitted rather than continuing to
+    /// 1.0. The step size is determined by the quantile being iterated to in the same way as for
+    /// `iter_quantiles`, so the steps become finer as the quantile approaches 1.0. This is handy
+    /// when only the tail of the distribution is of interest.
+    ///
+    /// `ticks_per_half_distance` is treated as for `iter_quantiles`, and the quantiles must satisfy
+    /// `0.0 <= start_quantile < end_quantile <= 1.0`.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10000, 4).unwrap();
+    /// for i in 0..10000 {
+    ///     hist += i;
+    /// }
+    ///
+    /// let mut tail = hist.iter_quantiles_between(1, 0.9, 0.99);
+    /// let first = tail.next().unwrap();
+    /// assert_eq!(0.9, first.quantile_iterated_to());
+    /// assert_eq!(hist.value_at_quantile(0.9), first.value_iterated_to());
+    /// assert_eq!(0.99, tail.last().unwrap().quantile_iterated_to());
+    /// ```
+    pub fn iter_quantiles_between(
+        &self,
+        ticks_per_half_distance: u32,
+        start_quantile: f64,
+        end_quantile: f64,
+    ) -> HistogramIterator<'_, T, iterators::quantile::Iter<'_, T>> {
+        iterators::quantile::Iter::new_between(
+            self,
+            ticks_per_half_distance,
+            start_quantile,
+            end_quantile,
+        )
+    }
+
     /// Iterates through histogram values using linear value steps. The iteration is performed in
     /// steps of size `step`, each one yielding the count for all values in the preceeding value
     /// range of size `step`. The iterator terminates when all recorded histogram values are
     /// exhausted.
     ///
+    /// `step` must be at least 1, since otherwise the steps would never advance. If it is 0, the
+    /// iterator yields nothing.
+    ///
     /// The iterator yields an `iterators::IterationValue` struct.
     ///
     /// ```
@@ -1072,7 +2262,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(199, hist.quantile_below(199), hist.quantile_below(199), 0, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         199, hist.quantile_below(199), hist.quantile_below(199), 0, 1, 100
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
@@ -1088,7 +2280,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(599, hist.quantile_below(599), hist.quantile_below(599), 0, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         599, hist.quantile_below(599), hist.quantile_below(599), 0, 1, 500
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
@@ -1100,7 +2294,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(899, hist.quantile_below(899), hist.quantile_below(899), 0, 2))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         899, hist.quantile_below(899), hist.quantile_below(899), 0, 2, 1650
+    ///     ))
     /// );
     /// assert_eq!(perc.next(), None);
     /// ```
@@ -1112,6 +2308,9 @@ impl<T: Counter> Histogram<T> {
     /// performed in steps that start at `start` and increase exponentially according to `exp`. The
     /// iterator terminates when all recorded histogram values are exhausted.
     ///
+    /// `start` must be at least 1, and `exp` must be greater than 1.0, since otherwise the steps
+    /// would never grow. If either is not the case, the iterator yields nothing.
+    ///
     /// The iterator yields an `iterators::IterationValue` struct.
     ///
     /// ```
@@ -1138,7 +2337,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(999, hist.quantile_below(999), hist.quantile_below(999), 0, 4))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         999, hist.quantile_below(999), hist.quantile_below(999), 0, 4, 2250
+    ///     ))
     /// );
     /// assert_eq!(perc.next(), None);
     /// ```
@@ -1164,19 +2365,27 @@ impl<T: Counter> Histogram<T> {
     /// let mut perc = hist.iter_recorded();
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(100, hist.quantile_below(100), hist.quantile_below(100), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         100, hist.quantile_below(100), hist.quantile_below(100), 1, 1, 100
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(500, hist.quantile_below(500), hist.quantile_below(500), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         500, hist.quantile_below(500), hist.quantile_below(500), 1, 1, 500
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(800, hist.quantile_below(800), hist.quantile_below(800), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         800, hist.quantile_below(800), hist.quantile_below(800), 1, 1, 800
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(850, hist.quantile_below(850), hist.quantile_below(850), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         850, hist.quantile_below(850), hist.quantile_below(850), 1, 1, 850
+    ///     ))
     /// );
     /// assert_eq!(perc.next(), None);
     /// ```
@@ -1184,12 +2393,193 @@ impl<T: Counter> Histogram<T> {
         iterators::recorded::Iter::new(self)
     }
 
+    /// Visit every recorded value like `iter_recorded`, calling `f` with the value iterated to,
+    /// the count at that value, and the count since the last visited value, as `IterationValue`'s
+    /// `value_iterated_to`, `count_at_value`, and `count_since_last_iteration` would be.
+    ///
+    /// This avoids constructing an `IterationValue` (and calculating its quantiles) at each step,
+    /// which adds up for tight loops over large histograms, especially with wide counter types.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// hist.record_n(100, 2).unwrap();
+    /// hist += 500;
+    ///
+    /// let mut visited = Vec::new();
+    /// hist.for_each_recorded(|value, count, since_last| visited.push((value, count, since_last)));
+    /// assert_eq!(vec![(100, 2, 2), (500, 1, 1)], visited);
+    /// ```
+    pub fn for_each_recorded<F: FnMut(u64, T, u64)>(&self, mut f: F) {
+        if self.total_count == 0 {
+            return;
+        }
+
+        // indexing is safe: len_to_max is at most the length of the counts array
+        for (index, &count) in self.counts[..self.len_to_max()].iter().enumerate() {
+            if count != T::zero() {
+                // zero counts are skipped, so the count since the last value is just this one's
+                f(
+                    self.highest_equivalent(self.value_for(index)),
+                    count,
+                    count.as_u64(),
+                );
+            }
+        }
+    }
+
+    /// Iterate through all recorded histogram values like `iter_recorded`, but report the chosen
+    /// representative of each bucket as the value iterated to, rather than always the highest
+    /// equivalent value.
+    ///
+    /// Re-recording the `(value, count)` pairs from this iterator reproduces the same bucket
+    /// counts whichever representative is chosen, so this makes it explicit which values such a
+    /// round trip goes through.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// use hdrhistogram::iterators::recorded::ValueRepr;
+    /// let mut hist = Histogram::<u64>::new_with_max(100_000, 2).unwrap();
+    /// hist += 10_000;
+    ///
+    /// let values = |repr| {
+    ///     hist.iter_recorded_repr(repr)
+    ///         .map(|v| v.value_iterated_to())
+    ///         .collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(vec![9984], values(ValueRepr::Lowest));
+    /// assert_eq!(vec![10_016], values(ValueRepr::Median));
+    /// assert_eq!(vec![10_047], values(ValueRepr::Highest));
+    /// ```
+    pub fn iter_recorded_repr(
+        &self,
+        repr: iterators::recorded::ValueRepr,
+    ) -> HistogramIterator<'_, T, iterators::recorded::ReprIter<'_, T>> {
+        iterators::recorded::ReprIter::new(self, repr)
+    }
+
+    /// Iterate through histogram values using a custom `PickyIterator`, which decides which buckets
+    /// to yield a value for. This is how all the other iterators are implemented, so it allows
+    /// for iteration strategies beyond the provided ones.
+    ///
+    /// The iterator yields an `iterators::IterationValue` struct.
+    ///
+    /// For example, this picker yields every bucket, like `iter_all`:
+    ///
+    /// ```
+    /// use hdrhistogram::{Counter, Histogram};
+    /// use hdrhistogram::iterators::{PickMetadata, PickyIterator};
+    ///
+    /// struct EveryBucket {
+    ///     visited: Option<usize>,
+    /// }
+    ///
+    /// impl<T: Counter> PickyIterator<T> for EveryBucket {
+    ///     fn pick(&mut self, index: usize, _: u64, _: T) -> Option<PickMetadata> {
+    ///         // pick() is called with the same index until it returns None
+    ///         if self.visited == Some(index) {
+    ///             return None;
+    ///         }
+    ///         self.visited = Some(index);
+    ///         Some(PickMetadata::new(None, None))
+    ///     }
+    ///
+    ///     fn more(&mut self, _: usize) -> bool {
+    ///         // keep going past the last recorded value, to the end of the histogram
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut hist = Histogram::<u64>::new_with_max(10, 1).unwrap();
+    /// hist += 1;
+    /// hist += 5;
+    /// hist += 5;
+    ///
+    /// let custom = hist.iter_with_picker(EveryBucket { visited: None }).collect::<Vec<_>>();
+    /// assert_eq!(hist.iter_all().collect::<Vec<_>>(), custom);
+    /// ```
+    pub fn iter_with_picker<P: iterators::PickyIterator<T>>(
+        &self,
+        picker: P,
+    ) -> HistogramIterator<'_, T, P> {
+        HistogramIterator::new(self, picker)
+    }
+
+    /// Iterate over the empirical cumulative distribution function, sampled at each recorded
+    /// value.
+    ///
+    /// This yields `(value, probability)` pairs for the same buckets as `iter_recorded`, where
+    /// `value` is the highest value equivalent to the bucket and `probability` is the fraction of
+    /// all recorded samples at or below it. The last probability is 1.0, and nothing is yielded for
+    /// an empty histogram.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    /// hist += 100;
+    /// hist += 500;
+    /// hist += 500;
+    /// hist += 800;
+    ///
+    /// let cdf: Vec<(u64, f64)> = hist.iter_cdf().collect();
+    /// assert_eq!(vec![(100, 0.25), (500, 0.75), (800, 1.0)], cdf);
+    /// ```
+    pub fn iter_cdf(&self) -> impl Iterator<Item = (u64, f64)> + '_ {
+        self.iter_recorded()
+            .map(|v| (v.value_iterated_to(), v.quantile()))
+    }
+
+    /// Iterate over the top-level buckets of the histogram (see `buckets`), rather than the
+    /// individual sub-buckets the other iterators visit.
+    ///
+    /// Each `TopLevelBucket` has the range of values the bucket covers and the total count of its
+    /// sub-buckets. As described in the `Histogram` documentation, bucket 0 covers the values up to
+    /// twice the sub-bucket count (in units of the lowest discernible value), and each following
+    /// bucket covers twice the range of the previous one at half the resolution.
+    ///
+    /// ```
+    /// use hdrhistogram::{Histogram, TopLevelBucket};
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// hist += 100;
+    /// hist += 3000;
+    ///
+    /// let buckets = hist.iter_top_level_buckets().collect::<Vec<_>>();
+    /// assert_eq!(hist.buckets() as usize, buckets.len());
+    /// assert_eq!(TopLevelBucket { index: 0, low: 0, high: 2047, count: 1 }, buckets[0]);
+    /// assert_eq!(TopLevelBucket { index: 1, low: 2048, high: 4095, count: 1 }, buckets[1]);
+    /// ```
+    pub fn iter_top_level_buckets(&self) -> impl Iterator<Item = TopLevelBucket> + '_ {
+        let half_count = self.sub_bucket_half_count as usize;
+        (0..self.bucket_count).map(move |index| {
+            // Bucket 0 uses all of its sub-buckets, but the others only use their top half, as
+            // the bottom half is covered by the previous buckets.
+            let start = if index == 0 {
+                0
+            } else {
+                (usize::from(index) + 1) * half_count
+            };
+            let end = (usize::from(index) + 2) * half_count;
+            // indexing is safe: the counts array has room for all bucket_count buckets
+            let count = self.counts[start..end]
+                .iter()
+                .fold(0_u64, |total, c| total.saturating_add(c.as_u64()));
+
+            TopLevelBucket {
+                index,
+                low: self.value_for(start),
+                high: self.highest_equivalent(self.value_for(end - 1)),
+                count,
+            }
+        })
+    }
+
     /// Iterates through all histogram values using the finest granularity steps supported by the
     /// underlying representation. The iteration steps through all possible unit value levels,
     /// regardless of whether or not there were recorded values for that value level, and
     /// terminates when all recorded histogram values are exhausted.
     ///
-    /// The iterator yields an `iterators::IterationValue` struct.
+    /// The iterator yields an `iterators::IterationValue` struct. It is double-ended, so `.rev()`
+    /// iterates from the highest value down.
     ///
     /// ```
     /// use hdrhistogram::Histogram;
@@ -1203,7 +2593,9 @@ impl<T: Counter> Histogram<T> {
     /// assert_eq!(perc.next(), Some(IterationValue::new(0, 0.0, 0.0, 0, 0)));
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(1, hist.quantile_below(1), hist.quantile_below(1), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         1, hist.quantile_below(1), hist.quantile_below(1), 1, 1, 1
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
@@ -1219,7 +2611,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(5, hist.quantile_below(5), hist.quantile_below(5), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         5, hist.quantile_below(5), hist.quantile_below(5), 1, 1, 5
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
@@ -1231,7 +2625,9 @@ impl<T: Counter> Histogram<T> {
     /// );
     /// assert_eq!(
     ///     perc.next(),
-    ///     Some(IterationValue::new(8, hist.quantile_below(8), hist.quantile_below(8), 1, 1))
+    ///     Some(IterationValue::new_with_value_sum(
+    ///         8, hist.quantile_below(8), hist.quantile_below(8), 1, 1, 8
+    ///     ))
     /// );
     /// assert_eq!(
     ///     perc.next(),
@@ -1243,6 +2639,179 @@ impl<T: Counter> Histogram<T> {
         iterators::all::Iter::new(self)
     }
 
+    /// Produce a textual percentile distribution, one line per step of `iter_quantiles`.
+    ///
+    /// This mirrors the Java implementation's `outputPercentileDistribution`: each line has the
+    /// value iterated to (divided by `output_value_unit_scaling_ratio`), the quantile iterated to,
+    /// the total count up to that value, and `1/(1-quantile)`. The last line, at quantile 1.0,
+    /// omits the `1/(1-quantile)` column. Lines do not include a trailing newline.
+    ///
+    /// Lines are formatted lazily, so this can be streamed into a `Write` without building the
+    /// entire distribution in memory.
+    ///
+    /// `ticks_per_half_distance` has the same meaning as for `iter_quantiles`.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// hist += 1000;
+    ///
+    /// let lines: Vec<String> = hist.percentile_distribution_lines(5, 1.0).collect();
+    /// assert_eq!("    1000.000 0.000000000000          1           1.00", lines[0]);
+    /// assert_eq!("    1000.000 1.000000000000          1", lines[1]);
+    /// ```
+    pub fn percentile_distribution_lines(
+        &self,
+        ticks_per_half_distance: u32,
+        output_value_unit_scaling_ratio: f64,
+    ) -> impl Iterator<Item = String> + '_ {
+        let value_precision = usize::from(self.significant_value_digits);
+        let mut total_count: u64 = 0;
+        self.iter_quantiles(ticks_per_half_distance).map(move |v| {
+            total_count = total_count.saturating_add(v.count_since_last_iteration());
+            let value = v.value_iterated_to() as f64 / output_value_unit_scaling_ratio;
+            let quantile = v.quantile_iterated_to();
+            if quantile < 1.0 {
+                format!(
+                    "{:12.*} {:2.12} {:10} {:14.2}",
+                    value_precision,
+                    value,
+                    quantile,
+                    total_count,
+                    1.0 / (1.0 - quantile)
+                )
+            } else {
+                format!(
+                    "{:12.*} {:2.12} {:10}",
+                    value_precision, value, quantile, total_count
+                )
+            }
+        })
+    }
+
+    /// Write a textual percentile distribution table to `w`, like the Java implementation's
+    /// `outputPercentileDistribution`.
+    ///
+    /// The table has a header naming the `Value`, `Percentile`, `TotalCount`, and
+    /// `1/(1-Percentile)` columns, the lines produced by [`percentile_distribution_lines`], and a
+    /// footer with the mean, standard deviation, max, total count, and bucket configuration.
+    /// Values (including the mean, standard deviation, and max) are divided by `value_scale`, e.g.
+    /// 1000.0 to print microsecond samples as milliseconds.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+    /// hist += 1000;
+    ///
+    /// let mut out = Vec::new();
+    /// hist.write_percentile_distribution(&mut out, 5, 1000.0).unwrap();
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.contains("#[Max     =        1.000, Total count    =            1]"));
+    /// ```
+    ///
+    /// [`percentile_distribution_lines`]: #method.percentile_distribution_lines
+    pub fn write_percentile_distribution<W: io::Write>(
+        &self,
+        w: &mut W,
+        ticks_per_half_distance: u32,
+        value_scale: f64,
+    ) -> io::Result<()> {
+        let value_precision = usize::from(self.significant_value_digits);
+
+        writeln!(
+            w,
+            "{:>12} {:>14} {:>10} {:>14}\n",
+            "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+        )?;
+        for line in self.percentile_distribution_lines(ticks_per_half_distance, value_scale) {
+            writeln!(w, "{}", line)?;
+        }
+        writeln!(
+            w,
+            "#[Mean    = {:12.*}, StdDeviation   = {:12.*}]",
+            value_precision,
+            self.mean() / value_scale,
+            value_precision,
+            self.stdev() / value_scale
+        )?;
+        writeln!(
+            w,
+            "#[Max     = {:12.*}, Total count    = {:12}]",
+            value_precision,
+            self.max() as f64 / value_scale,
+            self.len()
+        )?;
+        writeln!(
+            w,
+            "#[Buckets = {:12}, SubBuckets     = {:12}]",
+            self.bucket_count, self.sub_bucket_count
+        )
+    }
+
+    /// Write the percentile distribution to `w` in the `.hgrm` format that the Java
+    /// implementation's `outputPercentileDistribution` produces, with unscaled values.
+    ///
+    /// This is the format consumed by plotting tools such as `hdr-plot` and the online HdrHistogram
+    /// plotter, so the output can be fed to them directly. It is the same as
+    /// `write_percentile_distribution` with a `value_scale` of 1.0; use that to print values in
+    /// other units.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+    /// hist += 1000;
+    ///
+    /// let mut out = Vec::new();
+    /// hist.write_hgrm(&mut out, 5).unwrap();
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.starts_with("       Value     Percentile TotalCount 1/(1-Percentile)\n\n"));
+    /// ```
+    pub fn write_hgrm<W: io::Write>(
+        &self,
+        w: &mut W,
+        ticks_per_half_distance: u32,
+    ) -> io::Result<()> {
+        self.write_percentile_distribution(w, ticks_per_half_distance, 1.0)
+    }
+
+    /// Serialize this histogram in the V2 format and encode it as standard base64, like the
+    /// histograms in interval logs. This is handy for embedding histograms in text, e.g. JSON.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+    /// hist += 1000;
+    ///
+    /// let encoded = hist.to_base64().unwrap();
+    /// assert_eq!(hist, Histogram::<u64>::from_base64(&encoded).unwrap());
+    /// ```
+    #[cfg(feature = "serialization")]
+    pub fn to_base64(&self) -> Result<String, serialization::V2SerializeError> {
+        use base64::Engine as _;
+        use serialization::Serializer as _;
+
+        let mut buf = Vec::new();
+        let _ = serialization::V2Serializer::new().serialize(self, &mut buf)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(buf))
+    }
+
+    /// Decode a standard base64 string and deserialize the histogram it contains, such as one
+    /// produced by `to_base64` or read from an interval log.
+    ///
+    /// Any of the formats `Deserializer` supports can be used, not just V2. See
+    /// `Base64DeserializeError` for error conditions.
+    #[cfg(feature = "serialization")]
+    pub fn from_base64(s: &str) -> Result<Histogram<T>, serialization::Base64DeserializeError> {
+        use base64::Engine as _;
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(s)
+            .map_err(|_| serialization::Base64DeserializeError::InvalidBase64)?;
+        serialization::Deserializer::new()
+            .deserialize(&mut bytes.as_slice())
+            .map_err(serialization::Base64DeserializeError::DeserializeError)
+    }
+
     // ********************************************************************************************
     // Data statistics
     // ********************************************************************************************
@@ -1282,6 +2851,37 @@ impl<T: Counter> Histogram<T> {
         }
     }
 
+    /// Get the index of the highest bucket with a non-zero count, i.e. the bucket `max()` is in.
+    /// Returns `None` if the histogram has no recorded values.
+    ///
+    /// This is derived from the tracked max value, so it doesn't scan the counts.
+    pub fn max_nonzero_index(&self) -> Option<usize> {
+        if self.total_count == 0 {
+            return None;
+        }
+        self.index_for(self.max_value)
+    }
+
+    /// Get the index of the lowest bucket with a non-zero count. This is 0 if values in the lowest
+    /// bucket (e.g. 0) have been recorded, and otherwise the bucket `min_nz()` is in. Returns `None`
+    /// if the histogram has no recorded values.
+    ///
+    /// This is derived from the tracked min value, so it doesn't scan the counts.
+    pub fn min_nonzero_index(&self) -> Option<usize> {
+        if self.total_count == 0 {
+            return None;
+        }
+        if self
+            .count_at_index(0)
+            .expect("counts array must be non-empty")
+            != T::zero()
+        {
+            Some(0)
+        } else {
+            self.index_for(self.min_non_zero_value)
+        }
+    }
+
     /// Determine if two values are equivalent with the histogram's resolution. Equivalent here
     /// means that value samples recorded for any two equivalent values are counted in a common
     /// total count.
@@ -1289,6 +2889,35 @@ impl<T: Counter> Histogram<T> {
         self.lowest_equivalent(value1) == self.lowest_equivalent(value2)
     }
 
+    /// Determine if a value would be counted compatibly in this histogram and another one, which
+    /// may have a different resolution.
+    ///
+    /// Returns true if both histograms can track `value` (either because it is within their
+    /// current range, or because they auto-resize) and every value that `other` considers
+    /// equivalent to `value` is also equivalent to it in this histogram, i.e. this histogram's
+    /// resolution at `value` is at least as coarse as `other`'s. Samples near `value` can then be
+    /// moved from `other` into this histogram (as `add` does) without being split across buckets.
+    ///
+    /// Requiring the lowest equivalent values to be equal instead would be too strict: a value in
+    /// a bucket of width 1 in `other` may well be in a bucket of width 512 here. The check is not
+    /// symmetric, though; it holds in both directions only if both histograms have the same bucket
+    /// for `value`.
+    pub fn value_equivalent_in<F: Counter>(&self, value: u64, other: &Histogram<F>) -> bool {
+        let can_track = |auto_resize: bool, top: u64| auto_resize || value <= top;
+        if !can_track(
+            self.auto_resize,
+            self.highest_equivalent(self.value_for(self.last_index())),
+        ) || !can_track(
+            other.auto_resize,
+            other.highest_equivalent(other.value_for(other.last_index())),
+        ) {
+            return false;
+        }
+
+        self.equivalent(value, other.lowest_equivalent(value))
+            && self.equivalent(value, other.highest_equivalent(value))
+    }
+
     /// Get the computed mean value of all recorded values in the histogram.
     pub fn mean(&self) -> f64 {
         if self.total_count == 0 {
@@ -1318,6 +2947,74 @@ impl<T: Counter> Histogram<T> {
         (geom_dev_tot / self.total_count as f64).sqrt()
     }
 
+    /// Summarize the histogram as at most `max_centroids` `(mean_value, count)` centroids, in
+    /// increasing value order.
+    ///
+    /// Each recorded bucket (represented by its median equivalent value, as in `mean`) starts out
+    /// as its own centroid. If there are more recorded buckets than `max_centroids`, adjacent
+    /// buckets are merged so that each centroid covers a roughly equal share of the total count,
+    /// and the centroid's value is the count-weighted mean of its buckets. This is not a full
+    /// t-digest, but is a reasonable input for tools that compare against t-digest summaries.
+    ///
+    /// The centroid counts add up to `len()` (unless the total count has saturated). Returns an
+    /// empty `Vec` if the histogram is empty or `max_centroids` is 0.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// hist.record_n(100, 3).unwrap();
+    /// hist.record_n(200, 1).unwrap();
+    ///
+    /// assert_eq!(vec![(100.0, 3), (200.0, 1)], hist.centroids(10));
+    /// assert_eq!(vec![(125.0, 4)], hist.centroids(1));
+    /// ```
+    pub fn centroids(&self, max_centroids: usize) -> Vec<(f64, u64)> {
+        let buckets: Vec<(u64, u64)> = self
+            .iter_recorded()
+            .map(|v| {
+                (
+                    self.median_equivalent(v.value_iterated_to()),
+                    v.count_at_value().as_u64(),
+                )
+            })
+            .collect();
+
+        if max_centroids == 0 {
+            return Vec::new();
+        }
+        if buckets.len() <= max_centroids {
+            return buckets
+                .into_iter()
+                .map(|(value, count)| (value as f64, count))
+                .collect();
+        }
+
+        // Assign each bucket to a centroid based on the count that precedes it, so that each
+        // centroid gets about 1/max_centroids of the total. The assignment is non-decreasing and
+        // always < max_centroids, so there are at most that many centroids.
+        let total: u128 = buckets.iter().map(|&(_, count)| u128::from(count)).sum();
+        let mut centroids: Vec<(f64, u64)> = Vec::with_capacity(max_centroids);
+        let mut current = 0;
+        let mut weighted_sum = 0.0_f64;
+        let mut centroid_count = 0_u64;
+        let mut preceding: u128 = 0;
+        for (value, count) in buckets {
+            let centroid = preceding * max_centroids as u128 / total;
+            if centroid != current && centroid_count > 0 {
+                centroids.push((weighted_sum / centroid_count as f64, centroid_count));
+                weighted_sum = 0.0;
+                centroid_count = 0;
+            }
+            current = centroid;
+            weighted_sum += value as f64 * count as f64;
+            centroid_count = centroid_count.saturating_add(count);
+            preceding += u128::from(count);
+        }
+        centroids.push((weighted_sum / centroid_count as f64, centroid_count));
+
+        centroids
+    }
+
     /// Get the value at a given percentile.
     ///
     /// This is simply `value_at_quantile` multiplied by 100.0. For best floating-point precision,
@@ -1331,33 +3028,61 @@ impl<T: Counter> Histogram<T> {
     /// When the given quantile is > 0.0, the value returned is the value that the given
     /// percentage of the overall recorded value entries in the histogram are either smaller than
     /// or equivalent to. When the given quantile is 0.0, the value returned is the value that
-    /// all value entries in the histogram are either larger than or equivalent to.
+    /// all value entries in the histogram are either larger than or equivalent to, which is the
+    /// same as `min()`. Quantiles below 0.0 are treated as 0.0, and quantiles above 1.0 as 1.0.
     ///
     /// Two values are considered "equivalent" if `self.equivalent` would return true.
     ///
     /// If the total count of the histogram has exceeded `u64::max_value()`, this will return
     /// inaccurate results.
     pub fn value_at_quantile(&self, quantile: f64) -> u64 {
-        // Cap at 1.0
-        let quantile = if quantile > 1.0 { 1.0 } else { quantile };
-
-        let fractional_count = quantile * self.total_count as f64;
-        // If we're part-way into the next highest int, we should use that as the count
-        let mut count_at_quantile = fractional_count.ceil() as u64;
+        let mut value = [0];
+        self.values_at_sorted_quantiles(&[quantile], &mut value);
+        value[0]
+    }
 
+    /// Get the value at the quantile given by the fraction `numerator / denominator`.
+    ///
+    /// This is like `value_at_quantile`, but the count of samples to reach is calculated as
+    /// `ceil(numerator * len() / denominator)` with exact integer math rather than floating point.
+    /// With very large total counts, `value_at_quantile` can be off by a few counts because of
+    /// floating-point rounding, which can land it in the next bucket; this avoids that, which is
+    /// useful when the quantile is naturally expressed as "N out of M" anyway.
+    ///
+    /// Fractions greater than 1 (or with a zero denominator) are treated as 1, and a zero
+    /// numerator behaves like a quantile of 0.0.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// for v in 1..=1000 {
+    ///     hist += v;
+    /// }
+    ///
+    /// assert_eq!(999, hist.value_at_fraction(999, 1000));
+    /// assert_eq!(hist.value_at_quantile(0.5), hist.value_at_fraction(1, 2));
+    /// ```
+    ///
+    /// If the total count of the histogram has exceeded `u64::max_value()`, this will return
+    /// inaccurate results.
+    pub fn value_at_fraction(&self, numerator: u64, denominator: u64) -> u64 {
+        let count_at_fraction = if denominator == 0 || numerator >= denominator {
+            self.total_count
+        } else {
+            // won't overflow, and the result is <= total_count since numerator < denominator
+            let scaled = u128::from(numerator) * u128::from(self.total_count);
+            let d = u128::from(denominator);
+            (scaled / d + u128::from(scaled % d != 0)) as u64
+        };
         // Make sure we at least reach the first recorded entry
-        if count_at_quantile == 0 {
-            count_at_quantile = 1;
-        }
+        let count_at_fraction = cmp::max(count_at_fraction, 1);
 
         let mut total_to_current_index: u64 = 0;
-        for i in 0..self.counts.len() {
-            // Direct indexing is safe; indexes must reside in counts array.
-            // TODO overflow
-            total_to_current_index += self.counts[i].as_u64();
-            if total_to_current_index >= count_at_quantile {
+        for (i, c) in self.counts.iter().enumerate() {
+            total_to_current_index = total_to_current_index.saturating_add(c.as_u64());
+            if total_to_current_index >= count_at_fraction {
                 let value_at_index = self.value_for(i);
-                return if quantile == 0.0 {
+                return if numerator == 0 {
                     self.lowest_equivalent(value_at_index)
                 } else {
                     self.highest_equivalent(value_at_index)
@@ -1368,6 +3093,26 @@ impl<T: Counter> Histogram<T> {
         0
     }
 
+    /// Get the values at a standard set of percentiles commonly used for dashboards and reports:
+    /// p50, p75, p90, p95, p99, p99.9, p99.99, and the max.
+    ///
+    /// Each value is the same as what `value_at_quantile` would return for the corresponding
+    /// quantile, but they are all computed in a single pass over the histogram.
+    pub fn standard_percentiles(&self) -> StandardPercentiles {
+        let mut v = [0; 8];
+        self.values_at_sorted_quantiles(&[0.5, 0.75, 0.9, 0.95, 0.99, 0.999, 0.9999, 1.0], &mut v);
+        StandardPercentiles {
+            p50: v[0],
+            p75: v[1],
+            p90: v[2],
+            p95: v[3],
+            p99: v[4],
+            p99_9: v[5],
+            p99_99: v[6],
+            max: v[7],
+        }
+    }
+
     /// Get the percentile of samples at and below a given value.
     ///
     /// This is simply `quantile_below* multiplied by 100.0. For best floating-point precision, use
@@ -1376,6 +3121,22 @@ impl<T: Counter> Histogram<T> {
         self.quantile_below(value) * 100.0
     }
 
+    /// Get the per-mille (0 to 1000) of samples at or below a given value.
+    ///
+    /// This is the same quantity as `quantile_below`, scaled to 1000, but computed entirely in
+    /// integer arithmetic as `count_below * 1000 / len()`, rounding down. Unlike
+    /// `(percentile_below(value) * 10.0).floor()` it is never off by one due to float error, which
+    /// makes it suitable for exact bucketing into per-mille bins. An empty histogram yields 1000,
+    /// matching `quantile_below`.
+    pub fn permille_below(&self, value: u64) -> u64 {
+        if self.total_count == 0 {
+            return 1000;
+        }
+
+        let (_, count) = self.quantile_and_count_below(value);
+        (u128::from(count) * 1000 / u128::from(self.total_count)) as u64
+    }
+
     /// Get the quantile of samples at or below a given value.
     ///
     /// The value returned is the quantile of values recorded in the histogram that are
@@ -1389,8 +3150,19 @@ impl<T: Counter> Histogram<T> {
     /// If the total count of the histogram has reached `u64::max_value()`, this will return
     /// inaccurate results.
     pub fn quantile_below(&self, value: u64) -> f64 {
+        self.quantile_and_count_below(value).0
+    }
+
+    /// Get the quantile of samples at or below a given value like `quantile_below`, along with
+    /// the count of those samples, in a single scan.
+    ///
+    /// The count is the same as `count_between(0, value)`, and the quantile is that count divided
+    /// by `len()`. As with `quantile_below`, the results are inaccurate if the total count has
+    /// saturated at `u64::max_value()`; the count saturates too. An empty histogram yields
+    /// `(1.0, 0)`.
+    pub fn quantile_and_count_below(&self, value: u64) -> (f64, u64) {
         if self.total_count == 0 {
-            return 1.0;
+            return (1.0, 0);
         }
 
         let target_index = self.index_for_or_last(value);
@@ -1416,7 +3188,131 @@ impl<T: Counter> Histogram<T> {
         } else {
             self.total_count - total_to_current_index
         };
-        total_to_current_index.as_f64() / self.total_count as f64
+        (
+            total_to_current_index.as_f64() / self.total_count as f64,
+            total_to_current_index,
+        )
+    }
+
+    /// Get the quantile of samples at or below each of the given values.
+    ///
+    /// This is equivalent to calling `quantile_below` for each value, with the results in the same
+    /// order as `values`, but only makes a single pass over the histogram. `values` need not be
+    /// sorted, and may contain duplicates.
+    pub fn quantiles_of_values(&self, values: &[u64]) -> Vec<f64> {
+        if self.total_count == 0 {
+            return vec![1.0; values.len()];
+        }
+
+        // (target index, position in values), visited in index order
+        let mut targets = values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (self.index_for_or_last(value), i))
+            .collect::<Vec<_>>();
+        targets.sort_unstable();
+
+        let mut quantiles = vec![0.0; values.len()];
+        // number of indexes whose counts have been added to total_to_current_index so far
+        let mut indexes_consumed = 0;
+        let mut total_to_current_index: u64 = 0;
+        for (target_index, i) in targets {
+            while indexes_consumed <= target_index {
+                // Direct indexing is safe; index_for_or_last stays inside the counts array.
+                total_to_current_index =
+                    total_to_current_index.saturating_add(self.counts[indexes_consumed].as_u64());
+                indexes_consumed += 1;
+            }
+            quantiles[i] = total_to_current_index as f64 / self.total_count as f64;
+        }
+
+        quantiles
+    }
+
+    /// Take a snapshot of the cumulative counts of this histogram, for answering many quantile
+    /// queries quickly.
+    ///
+    /// `value_at_quantile` and `quantile_below` are O(n) in the number of buckets. Building the
+    /// snapshot is O(n) as well, but after that its `value_at_quantile` is an O(log n) binary search
+    /// and its `quantile_below` is O(1), with the same answers as the histogram's methods. This
+    /// pays off when querying many thresholds against a histogram that isn't changing.
+    ///
+    /// ```
+    /// use hdrhistogram::Histogram;
+    /// let mut hist = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+    /// for v in 1..=1000 {
+    ///     hist += v;
+    /// }
+    ///
+    /// let cumulative = hist.cumulative_counts();
+    /// assert_eq!(hist.value_at_quantile(0.99), cumulative.value_at_quantile(0.99));
+    /// assert_eq!(hist.quantile_below(500), cumulative.quantile_below(500));
+    /// ```
+    pub fn cumulative_counts(&self) -> CumulativeCounts<'_, T> {
+        let cumulative = self
+            .counts
+            .iter()
+            .scan(0u64, |total, c| {
+                *total = total.saturating_add(c.as_u64());
+                Some(*total)
+            })
+            .collect();
+        CumulativeCounts {
+            histogram: self,
+            cumulative,
+        }
+    }
+
+    /// Draw a random value distributed like the values recorded in this histogram.
+    ///
+    /// This picks a random sample among the `len()` recorded ones and returns the
+    /// `median_equivalent` of its bucket, i.e. inverse transform sampling. This is useful for
+    /// generating synthetic load that matches a recorded distribution. Returns 0 if the histogram
+    /// is empty.
+    ///
+    /// Each call scans the counts array; use `sample_n` to draw many values.
+    #[cfg(feature = "rand")]
+    pub fn sample<R: rand::Rng>(&self, rng: &mut R) -> u64 {
+        if self.total_count == 0 {
+            return 0;
+        }
+
+        let target = rng.gen_range(0..self.total_count);
+        let mut total_to_current_index: u64 = 0;
+        for (i, c) in self.counts.iter().enumerate() {
+            total_to_current_index = total_to_current_index.saturating_add(c.as_u64());
+            if total_to_current_index > target {
+                return self.median_equivalent(self.value_for(i));
+            }
+        }
+
+        // only reachable if total_count is out of sync with saturated counts
+        self.median_equivalent(self.max())
+    }
+
+    /// Draw `n` random values distributed like the values recorded in this histogram.
+    ///
+    /// This is like calling `sample` `n` times, but only scans the counts array once, after which
+    /// each value is found with a binary search. Returns `n` zeros if the histogram is empty.
+    #[cfg(feature = "rand")]
+    pub fn sample_n<R: rand::Rng>(&self, rng: &mut R, n: usize) -> Vec<u64> {
+        if self.total_count == 0 {
+            return vec![0; n];
+        }
+
+        let cumulative = self.cumulative_counts().cumulative;
+        (0..n)
+            .map(|_| {
+                let target = rng.gen_range(0..self.total_count);
+                let index = cumulative.partition_point(|&c| c <= target);
+                if index < cumulative.len() {
+                    self.median_equivalent(self.value_for(index))
+                } else {
+                    // as in `sample`, only if total_count is out of sync with saturated counts
+                    self.median_equivalent(self.max())
+                }
+            })
+            .collect()
     }
 
     /// Get the count of recorded values within a range of value levels (inclusive to within the
@@ -1441,6 +3337,20 @@ impl<T: Counter> Histogram<T> {
             .fold(0_u64, |t, v| t.saturating_add(v.as_u64()))
     }
 
+    /// Get the total count of all recorded values in the given range of bucket indices.
+    ///
+    /// This is the index-based counterpart of `count_between`, for use with index-oriented tools.
+    /// Valid indices are `0..distinct_values()`, and the range is clamped to that.
+    ///
+    /// The count will saturate at u64::max_value().
+    pub fn count_in_index_range(&self, range: ops::Range<usize>) -> u64 {
+        let end = cmp::min(range.end, self.counts.len());
+        let start = cmp::min(range.start, end);
+        self.counts[start..end]
+            .iter()
+            .fold(0_u64, |t, v| t.saturating_add(v.as_u64()))
+    }
+
     /// Get the count of recorded values at a specific value (to within the histogram resolution at
     /// the value level).
     ///
@@ -1454,6 +3364,41 @@ impl<T: Counter> Histogram<T> {
             .expect("index is <= last_index()")
     }
 
+    /// Get the count of recorded values in the bucket holding the value at a given quantile.
+    ///
+    /// This is equivalent to `count_at(value_at_quantile(quantile))`, and the quantile is handled
+    /// the same way as in `value_at_quantile`. Returns zero if the histogram is empty.
+    pub fn count_at_quantile(&self, quantile: f64) -> T {
+        if self.is_empty() {
+            return T::zero();
+        }
+
+        self.count_at(self.value_at_quantile(quantile))
+    }
+
+    /// Get an approximate probability density at a value: the fraction of recorded values in the
+    /// value's bucket, divided by the bucket's width.
+    ///
+    /// This is `count_at(value) / (equivalent_range(value) * len())`, so densities of buckets of
+    /// different widths are in the same units and can be plotted together. Multiplying the
+    /// density by `equivalent_range(value)` gives back the fraction of recorded values in the
+    /// bucket. Returns 0.0 if the histogram is empty.
+    ///
+    /// If the value is larger than the maximum representable value, it will be clamped to the
+    /// max representable value.
+    pub fn density_at(&self, value: u64) -> f64 {
+        if self.total_count == 0 {
+            return 0.0;
+        }
+
+        let index = self.index_for_or_last(value);
+        let count = self
+            .count_at_index(index)
+            .expect("index is <= last_index()");
+        let width = self.equivalent_range(self.value_for(index));
+        count.as_f64() / (width as f64 * self.total_count as f64)
+    }
+
     // ********************************************************************************************
     // Public helpers
     // ********************************************************************************************
@@ -1510,6 +3455,21 @@ impl<T: Counter> Histogram<T> {
         1_u64 << (self.unit_magnitude + bucket_index)
     }
 
+    /// Get the size of the range of values equivalent to the given value, relative to the value.
+    ///
+    /// This is the resolution the histogram provides at that value. For instance, if the value is
+    /// 1,000,000 and the equivalent range is 512, the relative error is 0.000512. For values of at
+    /// least `low() * 10^sigfig()`, this is at most `10^-sigfig()`.
+    ///
+    /// Returns `f64::INFINITY` for 0, as the range is infinitely large relative to it.
+    pub fn relative_error_at(&self, value: u64) -> f64 {
+        if value == 0 {
+            return f64::INFINITY;
+        }
+
+        self.equivalent_range(value) as f64 / value as f64
+    }
+
     /// Turn this histogram into a [`SyncHistogram`].
     #[cfg(feature = "sync")]
     pub fn into_sync(self) -> SyncHistogram<T> {
@@ -1552,6 +3512,68 @@ impl<T: Counter> Histogram<T> {
         self.value_from_loc(bucket_index as u8, sub_bucket_index)
     }
 
+    /// Compute `value_at_quantile` for each of the given quantiles in a single pass over the
+    /// counts, writing the results into the corresponding slots of `values`.
+    ///
+    /// `quantiles` must be sorted in ascending order, and be the same length as `values`.
+    fn values_at_sorted_quantiles(&self, quantiles: &[f64], values: &mut [u64]) {
+        debug_assert_eq!(quantiles.len(), values.len());
+        debug_assert!(quantiles.windows(2).all(|w| w[0] <= w[1]));
+
+        // number of indexes whose counts have been added to total_to_current_index so far
+        let mut indexes_consumed = 0;
+        let mut total_to_current_index: u64 = 0;
+        for (&quantile, value) in quantiles.iter().zip(values.iter_mut()) {
+            // Clamp to [0.0, 1.0], so that negative quantiles yield the min like 0.0 does
+            let quantile = quantile.clamp(0.0, 1.0);
+
+            let fractional_count = quantile * self.total_count as f64;
+            // If we're part-way into the next highest int, we should use that as the count
+            let mut count_at_quantile = fractional_count.ceil() as u64;
+
+            // Make sure we at least reach the first recorded entry
+            if count_at_quantile == 0 {
+                count_at_quantile = 1;
+            }
+
+            while total_to_current_index < count_at_quantile && indexes_consumed < self.counts.len()
+            {
+                // Direct indexing is safe; indexes must reside in counts array.
+                total_to_current_index =
+                    total_to_current_index.saturating_add(self.counts[indexes_consumed].as_u64());
+                indexes_consumed += 1;
+            }
+
+            *value = if total_to_current_index >= count_at_quantile {
+                // won't underflow: count_at_quantile >= 1, so at least one index was consumed
+                let value_at_index = self.value_for(indexes_consumed - 1);
+                if quantile == 0.0 {
+                    self.lowest_equivalent(value_at_index)
+                } else {
+                    self.highest_equivalent(value_at_index)
+                }
+            } else {
+                0
+            };
+        }
+    }
+
+    /// Add to the total count, saturating (and noting that it saturated) on overflow.
+    #[inline]
+    fn add_to_total_count(&mut self, count: T) {
+        // the count alone may not fit in a u64 with wide counter types
+        match count
+            .to_u64()
+            .and_then(|count| self.total_count.checked_add(count))
+        {
+            Some(total_count) => self.total_count = total_count,
+            None => {
+                self.total_count = u64::MAX;
+                self.total_count_saturated = true;
+            }
+        }
+    }
+
     /// Returns count at index, or None if out of bounds
     fn count_at_index(&self, index: usize) -> Option<T> {
         self.counts.get(index).cloned()
@@ -1664,11 +3686,45 @@ impl<T: Counter> Histogram<T> {
         // establish the new highest trackable value:
         self.highest_trackable_value = high;
 
+        // the initial allocation doesn't count as resizing
+        #[cfg(feature = "metrics")]
+        {
+            if len > self.counts.len() && !self.counts.is_empty() {
+                self.resize_count += 1;
+            }
+        }
+
         // expand counts to also hold the new counts
         self.counts.resize(len, T::zero());
         Ok(())
     }
 
+    /// Multiply `count` by `factor`, saturating at `T::max_value()`.
+    ///
+    /// This is done by doubling and adding, so that `Counter` doesn't need to support
+    /// multiplication, and is exact for counter types wider than `u64`.
+    fn saturating_mul_count(count: T, mut factor: u64) -> T {
+        let mut product = T::zero();
+        let mut addend = count;
+        while factor != 0 {
+            if factor & 1 == 1 {
+                product = match product.checked_add(&addend) {
+                    Some(p) => p,
+                    None => return T::max_value(),
+                };
+            }
+            factor >>= 1;
+            // a non-zero addend that overflows would overflow the product once a higher bit is set
+            if factor != 0 {
+                addend = match addend.checked_add(&addend) {
+                    Some(a) => a,
+                    None => return T::max_value(),
+                };
+            }
+        }
+        product
+    }
+
     /// Set internally tracked max_value to new value if new value is greater than current one.
     fn update_max(&mut self, value: u64) {
         let internal_value = value | self.unit_magnitude_mask; // Max unit-equivalent value
@@ -1690,6 +3746,24 @@ impl<T: Counter> Histogram<T> {
         }
     }
 
+    /// Widen the time window to cover the given start and end times, if any.
+    fn widen_time_window(
+        &mut self,
+        start_time: Option<time::SystemTime>,
+        end_time: Option<time::SystemTime>,
+    ) {
+        if let Some(start) = start_time {
+            if self.start_time.filter(|&t| t <= start).is_none() {
+                self.start_time = Some(start);
+            }
+        }
+        if let Some(end) = end_time {
+            if self.end_time.filter(|&t| t >= end).is_none() {
+                self.end_time = Some(end);
+            }
+        }
+    }
+
     fn update_min_max(&mut self, value: u64) {
         if value > self.max_value {
             self.update_max(value);
@@ -1712,6 +3786,15 @@ impl<T: Counter> Histogram<T> {
         };
     }
 
+    /// The length of the prefix of the counts array up to and including the bucket of the tracked
+    /// max value. All counts past it are zero, so scans for non-zero counts can stop there (and
+    /// `restat` too, as long as no counts have been added past the max since it was updated).
+    fn len_to_max(&self) -> usize {
+        self.index_for(self.max_value)
+            .map_or(self.distinct_values(), |i| i + 1)
+            .min(self.distinct_values())
+    }
+
     /// Recalculate min, max, total_count.
     fn restat(&mut self, length_to_scan: usize) {
         self.reset_max(ORIGINAL_MAX);
@@ -1732,12 +3815,154 @@ impl<T: Counter> Histogram<T> {
     }
 }
 
+/// How to correct for coordinated omission when recording, for `Histogram::record_n_correct_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionStrategy {
+    /// Backfill samples linearly down to the expected interval, like `Histogram::record_n_correct`.
+    Linear,
+    /// Don't correct at all, like `Histogram::record_n`.
+    None,
+}
+
+/// How a histogram handles values outside its range when recording, as set with
+/// `Histogram::on_out_of_range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    /// Return a `RecordError`, like `Histogram::record` with auto-resize disabled.
+    Error,
+    /// Clamp the value into range, like `Histogram::saturating_record`.
+    Clamp,
+    /// Resize the histogram to fit the value, like `Histogram::record` with auto-resize enabled.
+    Resize,
+}
+
+/// A top-level bucket of a histogram, as yielded by `Histogram::iter_top_level_buckets`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopLevelBucket {
+    /// The index of the bucket, in `[0, Histogram::buckets())`.
+    pub index: u8,
+    /// The lowest value in the bucket.
+    pub low: u64,
+    /// The highest value in the bucket.
+    pub high: u64,
+    /// The total count of the bucket's sub-buckets, saturating at `u64::max_value()`.
+    pub count: u64,
+}
+
+/// The values at a standard set of percentiles, as returned by `Histogram::standard_percentiles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StandardPercentiles {
+    /// The value at the 50th percentile (the median).
+    pub p50: u64,
+    /// The value at the 75th percentile.
+    pub p75: u64,
+    /// The value at the 90th percentile.
+    pub p90: u64,
+    /// The value at the 95th percentile.
+    pub p95: u64,
+    /// The value at the 99th percentile.
+    pub p99: u64,
+    /// The value at the 99.9th percentile.
+    pub p99_9: u64,
+    /// The value at the 99.99th percentile.
+    pub p99_99: u64,
+    /// The value at the 100th percentile (the max).
+    pub max: u64,
+}
+
+/// `(value, count)` entries accumulated to be recorded into a histogram all at once, as created by
+/// `Histogram::batch`.
+///
+/// Entries are only buffered by `add`. `commit` then records all of them like
+/// `Histogram::record_pairs_bulk`, resizing at most once for the largest value and updating the
+/// histogram's statistics once.
+#[derive(Debug, Clone, Default)]
+pub struct RecordBatch<T: Counter> {
+    pairs: Vec<(u64, T)>,
+}
+
+impl<T: Counter> RecordBatch<T> {
+    /// Create an empty batch.
+    pub fn new() -> RecordBatch<T> {
+        RecordBatch { pairs: Vec::new() }
+    }
+
+    /// Add `count` samples of `value` to the batch. Nothing is recorded until `commit`.
+    pub fn add(&mut self, value: u64, count: T) {
+        self.pairs.push((value, count));
+    }
+
+    /// Record every entry of the batch into `hist`.
+    ///
+    /// This is all-or-nothing: if any value cannot be recorded (see `RecordError`), `hist` is left
+    /// unchanged.
+    pub fn commit(self, hist: &mut Histogram<T>) -> Result<(), RecordError> {
+        hist.record_pairs_bulk(&self.pairs)
+    }
+}
+
+/// A snapshot of a histogram's cumulative counts, as returned by `Histogram::cumulative_counts`.
+///
+/// This holds the running total of counts up to and including each bucket, so quantile queries
+/// don't need to scan the counts. It borrows the histogram, so the histogram can't
+/// be modified while the snapshot is alive.
+#[derive(Debug, Clone)]
+pub struct CumulativeCounts<'a, T: Counter> {
+    histogram: &'a Histogram<T>,
+    // saturating running total of counts at each index
+    cumulative: Vec<u64>,
+}
+
+impl<'a, T: Counter> CumulativeCounts<'a, T> {
+    /// Get the value at a given quantile, in O(log n).
+    ///
+    /// This returns the same value as `Histogram::value_at_quantile`.
+    pub fn value_at_quantile(&self, quantile: f64) -> u64 {
+        let h = self.histogram;
+        // Clamp to [0.0, 1.0], like Histogram::value_at_quantile
+        let quantile = quantile.clamp(0.0, 1.0);
+
+        let fractional_count = quantile * h.total_count as f64;
+        // If we're part-way into the next highest int, we should use that as the count, and make
+        // sure we at least reach the first recorded entry
+        let count_at_quantile = cmp::max(fractional_count.ceil() as u64, 1);
+
+        // the running totals never decrease, so find the first index that reaches the count
+        let index = self.cumulative.partition_point(|&c| c < count_at_quantile);
+        if index == self.cumulative.len() {
+            return 0;
+        }
+
+        let value_at_index = h.value_for(index);
+        if quantile == 0.0 {
+            h.lowest_equivalent(value_at_index)
+        } else {
+            h.highest_equivalent(value_at_index)
+        }
+    }
+
+    /// Get the quantile of samples at or below a given value, in O(1).
+    ///
+    /// This returns the same quantile as `Histogram::quantile_below`.
+    pub fn quantile_below(&self, value: u64) -> f64 {
+        let h = self.histogram;
+        if h.total_count == 0 {
+            return 1.0;
+        }
+
+        // indexing is safe: index_for_or_last stays inside the counts array, which is as long as
+        // the cumulative counts
+        self.cumulative[h.index_for_or_last(value)] as f64 / h.total_count as f64
+    }
+}
+
 /// Stores the state to calculate the max, min, and total count for a histogram by iterating across
 /// the counts.
 struct RestatState<T: Counter> {
     max_index: Option<usize>,
     min_index: Option<usize>,
     total_count: u64,
+    total_count_saturated: bool,
     phantom: std::marker::PhantomData<T>,
 }
 
@@ -1747,6 +3972,7 @@ impl<T: Counter> RestatState<T> {
             max_index: None,
             min_index: None,
             total_count: 0,
+            total_count_saturated: false,
             phantom: std::marker::PhantomData,
         }
     }
@@ -1754,7 +3980,16 @@ impl<T: Counter> RestatState<T> {
     /// Should be called on every non-zero count found
     #[inline]
     fn on_nonzero_count(&mut self, index: usize, count: T) {
-        self.total_count = self.total_count.saturating_add(count.as_u64());
+        match count
+            .to_u64()
+            .and_then(|count| self.total_count.checked_add(count))
+        {
+            Some(total_count) => self.total_count = total_count,
+            None => {
+                self.total_count = u64::MAX;
+                self.total_count_saturated = true;
+            }
+        }
 
         self.max_index = Some(index);
 
@@ -1776,6 +4011,7 @@ impl<T: Counter> RestatState<T> {
         }
 
         h.total_count = self.total_count;
+        h.total_count_saturated = self.total_count_saturated;
     }
 }
 
@@ -1,5 +1,5 @@
-use super::{V2_COMPRESSED_COOKIE, V2_COOKIE};
-use crate::{Counter, Histogram, RestatState};
+use super::{V2_COMPRESSED_COOKIE_BASE, V2_COOKIE_BASE, V2_HEADER_SIZE};
+use crate::{AdditionError, Counter, Histogram, RestatState};
 use byteorder::{BigEndian, ReadBytesExt};
 use flate2::read::ZlibDecoder;
 use num_traits::ToPrimitive;
@@ -9,6 +9,7 @@ use std::{self, error, fmt};
 
 /// Errors that can happen during deserialization.
 #[derive(Debug)]
+#[allow(variant_size_differences)]
 pub enum DeserializeError {
     /// An i/o operation failed.
     IoError(io::Error),
@@ -26,6 +27,9 @@ pub enum DeserializeError {
     UsizeTypeTooSmall,
     /// The encoded array is longer than it should be for the histogram's value range.
     EncodedArrayTooLong,
+    /// The deserialized histogram could not be added to the accumulator passed to
+    /// `Deserializer::deserialize_and_add`.
+    AdditionFailed(AdditionError),
 }
 
 impl std::convert::From<std::io::Error> for DeserializeError {
@@ -62,6 +66,9 @@ impl fmt::Display for DeserializeError {
                 f,
                 "The encoded array is longer than it should be for the histogram's value range"
             ),
+            DeserializeError::AdditionFailed(e) => {
+                write!(f, "The histogram could not be added: {}", e)
+            }
         }
     }
 }
@@ -75,6 +82,39 @@ impl error::Error for DeserializeError {
     }
 }
 
+/// Errors that can happen when deserializing a base64-encoded histogram with
+/// `Histogram::from_base64`.
+#[derive(Debug)]
+#[allow(variant_size_differences)]
+pub enum Base64DeserializeError {
+    /// The string is not valid standard base64.
+    InvalidBase64,
+    /// The decoded bytes could not be deserialized.
+    DeserializeError(DeserializeError),
+}
+
+impl fmt::Display for Base64DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Base64DeserializeError::InvalidBase64 => {
+                write!(f, "The string is not valid standard base64")
+            }
+            Base64DeserializeError::DeserializeError(e) => {
+                write!(f, "The decoded histogram could not be deserialized: {}", e)
+            }
+        }
+    }
+}
+
+impl error::Error for Base64DeserializeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Base64DeserializeError::DeserializeError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 /// Deserializer for all supported formats.
 ///
 /// Since the serialization formats all include some magic bytes that allow reliable identification
@@ -105,19 +145,142 @@ impl Deserializer {
         &mut self,
         reader: &mut R,
     ) -> Result<Histogram<T>, DeserializeError> {
+        let header = self.read_payload(reader)?;
+        self.histogram_from_payload(&header)
+    }
+
+    /// Deserialize an encoded histogram from the provided reader like `deserialize`, and also
+    /// return the number of bytes it took up.
+    ///
+    /// The reader is left positioned just after the histogram's bytes, so this is handy for
+    /// parsing streams where histograms are followed by other data, e.g. length-prefixed records.
+    /// For a compressed histogram, the count is the compressed size.
+    pub fn deserialize_counted<T: Counter, R: Read>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<(Histogram<T>, usize), DeserializeError> {
+        let header = self.read_payload(reader)?;
+        let h = self.histogram_from_payload(&header)?;
+        Ok((h, header.encoded_len))
+    }
+
+    /// Deserialize an encoded histogram from the provided reader and add it to `accumulator`.
+    ///
+    /// The result is the same as deserializing the histogram and passing it to
+    /// `accumulator.add()`. When the encoded histogram has the same lowest discernible value and
+    /// significant digits as `accumulator`, its counts are added directly to `accumulator`
+    /// without creating a temporary histogram, which saves an allocation and a copy per histogram
+    /// when aggregating many of them. Otherwise, it falls back to deserializing and adding.
+    ///
+    /// If the counts cannot be added (see `AdditionError`), `DeserializeError::AdditionFailed` is
+    /// returned. On error, `accumulator` is left unchanged.
+    pub fn deserialize_and_add<T: Counter, R: Read>(
+        &mut self,
+        reader: &mut R,
+        accumulator: &mut Histogram<T>,
+    ) -> Result<(), DeserializeError> {
+        let header = self.read_payload(reader)?;
+        if header.low != accumulator.lowest_discernible_value
+            || header.num_digits != accumulator.significant_value_digits
+        {
+            let h: Histogram<T> = self.histogram_from_payload(&header)?;
+            return accumulator.add(h).map_err(DeserializeError::AdditionFailed);
+        }
+
+        // Same bucket layout, so indexes line up. The other parameters were already validated
+        // when accumulator was created.
+        if header.high < 2 * header.low {
+            return Err(DeserializeError::InvalidParameters);
+        }
+        let payload = &self.payload_buf[0..header.payload_len];
+
+        // Validate everything before modifying accumulator, so that it's unchanged on error.
+        let mut max_index = None;
+        decode_counts(payload, |index, count| {
+            let _ = T::from_i64(count).ok_or(DeserializeError::UnsuitableCounterType)?;
+            max_index = Some(index);
+            Ok(())
+        })?;
+        let max_index = match max_index {
+            Some(i) => i,
+            // nothing to add
+            None => return Ok(()),
+        };
+        if max_index >= accumulator.distinct_values() {
+            let last_possible_index = accumulator
+                .index_for(u64::MAX)
+                .ok_or(DeserializeError::UsizeTypeTooSmall)?;
+            if max_index > last_possible_index {
+                return Err(DeserializeError::EncodedArrayTooLong);
+            }
+            if !accumulator.auto_resize {
+                return Err(DeserializeError::AdditionFailed(
+                    AdditionError::OtherAddendValueExceedsRange,
+                ));
+            }
+            let high = accumulator.highest_equivalent(accumulator.value_for(max_index));
+            accumulator.resize(high).map_err(|_| {
+                DeserializeError::AdditionFailed(AdditionError::ResizeFailedUsizeTypeTooSmall)
+            })?;
+        }
+
+        let mut min_index = None;
+        decode_counts(payload, |index, count| {
+            let count = T::from_i64(count).expect("counts were already validated");
+            // indexing is safe: we've been resized to hold the max index
+            let c = &mut accumulator.counts[index];
+            *c = c.saturating_add(count);
+            accumulator.add_to_total_count(count);
+            // like restat, index 0 doesn't affect the min non-zero value
+            if min_index.is_none() && index != 0 {
+                min_index = Some(index);
+            }
+            Ok(())
+        })?;
+
+        let max = accumulator.highest_equivalent(accumulator.value_for(max_index));
+        accumulator.update_max(max);
+        if let Some(min_index) = min_index {
+            let min = accumulator.value_for(min_index);
+            accumulator.update_min(min);
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize every encoded histogram from the provided reader until it is exhausted.
+    ///
+    /// This is handy when several histograms have been serialized one after the other into the
+    /// same buffer or file. The returned iterator yields `None` once the reader is at EOF between
+    /// histograms, and `Some(Err(_))` if a histogram could not be deserialized (e.g. because EOF
+    /// was reached partway through it), after which it yields `None`.
+    pub fn deserialize_all<'a, T: Counter, R: Read>(
+        &'a mut self,
+        reader: &'a mut R,
+    ) -> DeserializeAllIter<'a, T, R> {
+        DeserializeAllIter {
+            deserializer: self,
+            reader,
+            done: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Read the cookie, header, and encoded counts of a histogram into `payload_buf`.
+    fn read_payload<R: Read>(&mut self, reader: &mut R) -> Result<V2Header, DeserializeError> {
         let cookie = reader.read_u32::<BigEndian>()?;
 
-        match cookie {
-            V2_COOKIE => self.deser_v2(reader),
-            V2_COMPRESSED_COOKIE => self.deser_v2_compressed(reader),
+        match cookie_base(cookie) {
+            V2_COOKIE_BASE => self.read_v2(reader),
+            V2_COMPRESSED_COOKIE_BASE => self.read_v2_compressed(reader),
             _ => Err(DeserializeError::InvalidCookie),
         }
     }
 
-    fn deser_v2_compressed<T: Counter, R: Read>(
+    fn read_v2_compressed<R: Read>(
         &mut self,
         reader: &mut R,
-    ) -> Result<Histogram<T>, DeserializeError> {
+    ) -> Result<V2Header, DeserializeError> {
         let payload_len = reader
             .read_u32::<BigEndian>()?
             .to_usize()
@@ -126,18 +289,18 @@ impl Deserializer {
         // TODO reuse deflate buf, or switch to lower-level flate2::Decompress
         let mut deflate_reader = ZlibDecoder::new(reader.take(payload_len as u64));
         let inner_cookie = deflate_reader.read_u32::<BigEndian>()?;
-        if inner_cookie != V2_COOKIE {
+        if cookie_base(inner_cookie) != V2_COOKIE_BASE {
             return Err(DeserializeError::InvalidCookie);
         }
 
-        self.deser_v2(&mut deflate_reader)
+        let mut header = self.read_v2(&mut deflate_reader)?;
+        // cookie and length, then the compressed bytes
+        header.encoded_len = 8 + payload_len;
+        Ok(header)
     }
 
     #[allow(clippy::float_cmp)]
-    fn deser_v2<T: Counter, R: Read>(
-        &mut self,
-        reader: &mut R,
-    ) -> Result<Histogram<T>, DeserializeError> {
+    fn read_v2<R: Read>(&mut self, reader: &mut R) -> Result<V2Header, DeserializeError> {
         let payload_len = reader
             .read_u32::<BigEndian>()?
             .to_usize()
@@ -157,46 +320,96 @@ impl Deserializer {
             return Err(DeserializeError::UnsupportedFeature);
         }
 
-        let mut h = Histogram::new_with_bounds(low, high, num_digits)
-            .map_err(|_| DeserializeError::InvalidParameters)?;
-
         if payload_len > self.payload_buf.len() {
             self.payload_buf.resize(payload_len, 0);
         }
 
-        let mut payload_slice = &mut self.payload_buf[0..payload_len];
-        reader.read_exact(&mut payload_slice)?;
+        reader.read_exact(&mut self.payload_buf[0..payload_len])?;
+
+        Ok(V2Header {
+            payload_len,
+            encoded_len: V2_HEADER_SIZE + payload_len,
+            num_digits,
+            low,
+            high,
+        })
+    }
+
+    /// Create a histogram from the encoded counts read by `read_payload`.
+    fn histogram_from_payload<T: Counter>(
+        &self,
+        header: &V2Header,
+    ) -> Result<Histogram<T>, DeserializeError> {
+        let mut h = Histogram::new_with_bounds(header.low, header.high, header.num_digits)
+            .map_err(|_| DeserializeError::InvalidParameters)?;
 
-        let mut payload_index: usize = 0;
         let mut restat_state = RestatState::new();
-        let mut decode_state = DecodeLoopState::new();
+        decode_counts(&self.payload_buf[0..header.payload_len], |index, count| {
+            let count: T = T::from_i64(count).ok_or(DeserializeError::UnsuitableCounterType)?;
+            h.set_count_at_index(index, count)
+                .map_err(|_| DeserializeError::EncodedArrayTooLong)?;
+            restat_state.on_nonzero_count(index, count);
+            Ok(())
+        })?;
 
-        while payload_index < payload_len.saturating_sub(9) {
-            // Read with fast loop until we are within 9 of the end. Fast loop can't handle EOF,
-            // so bail to slow version for the last few bytes.
+        restat_state.update_histogram(&mut h);
 
-            // payload_index math is safe because payload_len is a usize
-            let (zz_num, bytes_read) =
-                varint_read_slice(&payload_slice[payload_index..(payload_index + 9)]);
-            payload_index += bytes_read;
+        Ok(h)
+    }
+}
 
-            let count_or_zeros = zig_zag_decode(zz_num);
+/// The parts of a V2 header needed after the encoded counts have been read.
+struct V2Header {
+    payload_len: usize,
+    // the total size of the serialized histogram, including the cookie
+    encoded_len: usize,
+    num_digits: u8,
+    low: u64,
+    high: u64,
+}
 
-            decode_state.on_decoded_num(count_or_zeros, &mut restat_state, &mut h)?;
-        }
+/// Iterator over histograms read from a reader until EOF.
+///
+/// See `Deserializer::deserialize_all`.
+pub struct DeserializeAllIter<'a, T: Counter, R: Read> {
+    deserializer: &'a mut Deserializer,
+    reader: &'a mut R,
+    done: bool,
+    phantom: PhantomData<T>,
+}
 
-        // Now read the leftovers
-        let leftover_slice = &payload_slice[payload_index..];
-        let mut cursor = Cursor::new(&leftover_slice);
-        while cursor.position() < leftover_slice.len() as u64 {
-            let count_or_zeros = zig_zag_decode(varint_read(&mut cursor)?);
+impl<'a, T: Counter, R: Read> Iterator for DeserializeAllIter<'a, T, R> {
+    type Item = Result<Histogram<T>, DeserializeError>;
 
-            decode_state.on_decoded_num(count_or_zeros, &mut restat_state, &mut h)?;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
 
-        restat_state.update_histogram(&mut h);
-
-        Ok(h)
+        // Read the first byte separately so that EOF between histograms can be told apart from a
+        // truncated histogram.
+        let mut first = [0_u8; 1];
+        let r = loop {
+            match self.reader.read(&mut first) {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                r => break r,
+            }
+        };
+        let r = match r {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            }
+            Ok(_) => self
+                .deserializer
+                .deserialize(&mut (&first[..]).chain(&mut *self.reader)),
+            Err(e) => Err(DeserializeError::IoError(e)),
+        };
+
+        if r.is_err() {
+            self.done = true;
+        }
+        Some(r)
     }
 }
 
@@ -302,6 +515,15 @@ pub fn varint_read<R: Read>(reader: &mut R) -> io::Result<u64> {
     Ok(value)
 }
 
+/// Strip the word size bits from a cookie.
+///
+/// The Java implementation sets the word size bits (0x10 for V2), but HdrHistogram_c leaves them
+/// unset, so only the remaining bits identify the format. This matches Java's `getCookieBase`.
+#[inline]
+fn cookie_base(cookie: u32) -> u32 {
+    cookie & !0xf0
+}
+
 /// truncate byte to low 7 bits, cast to u64
 #[inline]
 fn low_7_bits(b: u8) -> u64 {
@@ -319,28 +541,16 @@ pub fn zig_zag_decode(encoded: u64) -> i64 {
     ((encoded >> 1) as i64) ^ -((encoded & 1) as i64)
 }
 
-/// We need to perform the same logic in two different decode loops while carrying over a modicum
-/// of state.
-struct DecodeLoopState<T: Counter> {
-    dest_index: usize,
-    phantom: PhantomData<T>,
-}
-
-impl<T: Counter> DecodeLoopState<T> {
-    fn new() -> DecodeLoopState<T> {
-        DecodeLoopState {
-            dest_index: 0,
-            phantom: PhantomData,
-        }
-    }
-
-    #[inline]
-    fn on_decoded_num(
-        &mut self,
-        count_or_zeros: i64,
-        restat_state: &mut RestatState<T>,
-        h: &mut Histogram<T>,
-    ) -> Result<(), DeserializeError> {
+/// Decode the encoded counts in `payload`, calling `on_count` with the index and count of each
+/// positive count.
+#[inline]
+fn decode_counts<F: FnMut(usize, i64) -> Result<(), DeserializeError>>(
+    payload: &[u8],
+    mut on_count: F,
+) -> Result<(), DeserializeError> {
+    let mut payload_index: usize = 0;
+    let mut dest_index: usize = 0;
+    let mut on_decoded_num = |count_or_zeros: i64| -> Result<(), DeserializeError> {
         if count_or_zeros < 0 {
             // For a valid histogram, negation won't overflow because you can't have anywhere close
             // to even 2^32 array length
@@ -348,27 +558,38 @@ impl<T: Counter> DecodeLoopState<T> {
                 .to_usize()
                 .ok_or(DeserializeError::UsizeTypeTooSmall)?;
             // skip the zeros
-            self.dest_index = self
-                .dest_index
+            dest_index = dest_index
                 .checked_add(zero_count)
                 .ok_or(DeserializeError::UsizeTypeTooSmall)?;
         } else {
-            let count: T =
-                T::from_i64(count_or_zeros).ok_or(DeserializeError::UnsuitableCounterType)?;
-
-            if count > T::zero() {
-                h.set_count_at_index(self.dest_index, count)
-                    .map_err(|_| DeserializeError::EncodedArrayTooLong)?;
-
-                restat_state.on_nonzero_count(self.dest_index, count);
+            if count_or_zeros > 0 {
+                on_count(dest_index, count_or_zeros)?;
             }
 
-            self.dest_index = self
-                .dest_index
+            dest_index = dest_index
                 .checked_add(1)
                 .ok_or(DeserializeError::UsizeTypeTooSmall)?;
         }
-
         Ok(())
+    };
+
+    while payload_index < payload.len().saturating_sub(9) {
+        // Read with fast loop until we are within 9 of the end. Fast loop can't handle EOF,
+        // so bail to slow version for the last few bytes.
+
+        // payload_index math is safe because payload.len() is a usize
+        let (zz_num, bytes_read) = varint_read_slice(&payload[payload_index..(payload_index + 9)]);
+        payload_index += bytes_read;
+
+        on_decoded_num(zig_zag_decode(zz_num))?;
     }
+
+    // Now read the leftovers
+    let leftover_slice = &payload[payload_index..];
+    let mut cursor = Cursor::new(&leftover_slice);
+    while cursor.position() < leftover_slice.len() as u64 {
+        on_decoded_num(zig_zag_decode(varint_read(&mut cursor)?))?;
+    }
+
+    Ok(())
 }
@@ -239,6 +239,7 @@ pub struct IntervalLogWriterBuilder {
     start_time: Option<f64>,
     base_time: Option<f64>,
     max_value_divisor: f64,
+    legend: bool,
 }
 
 impl Default for IntervalLogWriterBuilder {
@@ -255,6 +256,7 @@ impl IntervalLogWriterBuilder {
             start_time: None,
             base_time: None,
             max_value_divisor: 1.0,
+            legend: false,
         }
     }
 
@@ -300,6 +302,24 @@ impl IntervalLogWriterBuilder {
         self
     }
 
+    /// Set whether to write a legend line naming the columns of interval lines after the headers,
+    /// as the Java implementation's `HistogramLogWriter` does:
+    ///
+    /// ```text
+    /// "StartTimestamp","Interval_Length","Interval_Max","Interval_Compressed_Histogram"
+    /// ```
+    ///
+    /// `IntervalLogIterator` skips this line when parsing.
+    ///
+    /// If this is not set, no legend will be written.
+    ///
+    /// This can be called multiple times, but only the value for the most recent invocation will
+    /// be used.
+    pub fn with_legend(&mut self, enabled: bool) -> &mut Self {
+        self.legend = enabled;
+        self
+    }
+
     /// Build a LogWriter and apply any configured headers.
     #[allow(clippy::float_cmp)]
     pub fn begin_log_with<'a, 'b, W: 'a + io::Write, S: 'b + Serializer>(
@@ -342,6 +362,12 @@ impl IntervalLogWriterBuilder {
             ))?;
         }
 
+        if self.legend {
+            internal_writer.write_fmt(format_args!(
+                "\"StartTimestamp\",\"Interval_Length\",\"Interval_Max\",\"Interval_Compressed_Histogram\"\n"
+            ))?;
+        }
+
         Ok(IntervalLogWriter { internal_writer })
     }
 }
@@ -400,6 +426,33 @@ impl<'a, 'b, W: 'a + io::Write, S: 'b + Serializer> IntervalLogWriter<'a, 'b, W,
         self.internal_writer
             .write_histogram(h, start_timestamp, duration, tag)
     }
+
+    /// Write an interval histogram that has already been serialized and base64-encoded, such as
+    /// the `encoded_histogram()` of an `IntervalLogHistogram` read from another log.
+    ///
+    /// This avoids deserializing and re-serializing histograms when copying them between logs.
+    /// `base64_histogram` is written as-is, so it must not contain a newline.
+    ///
+    /// `max` is the maximum value as it should appear in the log, i.e. already divided by the max
+    /// value divisor if one is in use.
+    ///
+    /// See `write_histogram` for info on `start_timestamp`, `duration`, and `tag`.
+    pub fn write_raw_interval(
+        &mut self,
+        start_timestamp: time::Duration,
+        duration: time::Duration,
+        max: f64,
+        tag: Option<Tag>,
+        base64_histogram: &str,
+    ) -> io::Result<()> {
+        self.internal_writer.write_raw_interval(
+            start_timestamp,
+            duration,
+            max,
+            tag,
+            base64_histogram,
+        )
+    }
 }
 
 /// Errors that can occur while writing a log.
@@ -467,19 +520,12 @@ impl<'a, 'b, W: 'a + io::Write, S: 'b + Serializer> InternalLogWriter<'a, 'b, W,
         tag: Option<Tag>,
     ) -> Result<(), IntervalLogWriterError<S::SerializeError>> {
         self.serialize_buf.clear();
-        self.text_buf.clear();
 
-        if let Some(Tag(s)) = tag {
-            write!(self.text_buf, "Tag={},", &s).expect("Writes to a String can't fail");
-        }
-
-        write!(
-            self.writer,
-            "{}{:.3},{:.3},{:.3},",
-            self.text_buf,
-            duration_as_fp_seconds(start_timestamp),
-            duration_as_fp_seconds(duration),
-            h.max() as f64 / self.max_value_divisor // because the Java impl does it this way
+        self.write_interval_prefix(
+            start_timestamp,
+            duration,
+            h.max() as f64 / self.max_value_divisor, // because the Java impl does it this way
+            tag,
         )?;
 
         self.text_buf.clear();
@@ -495,6 +541,44 @@ impl<'a, 'b, W: 'a + io::Write, S: 'b + Serializer> InternalLogWriter<'a, 'b, W,
 
         Ok(())
     }
+
+    fn write_raw_interval(
+        &mut self,
+        start_timestamp: time::Duration,
+        duration: time::Duration,
+        max: f64,
+        tag: Option<Tag>,
+        base64_histogram: &str,
+    ) -> io::Result<()> {
+        self.write_interval_prefix(start_timestamp, duration, max, tag)?;
+
+        self.writer.write_all(base64_histogram.as_bytes())?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Write the tag, timestamps, and max of an interval line, up to the encoded histogram.
+    fn write_interval_prefix(
+        &mut self,
+        start_timestamp: time::Duration,
+        duration: time::Duration,
+        max: f64,
+        tag: Option<Tag>,
+    ) -> io::Result<()> {
+        self.text_buf.clear();
+
+        if let Some(Tag(s)) = tag {
+            write!(self.text_buf, "Tag={},", &s).expect("Writes to a String can't fail");
+        }
+
+        write!(
+            self.writer,
+            "{}{:.3},{:.3},{:.3},",
+            self.text_buf,
+            duration_as_fp_seconds(start_timestamp),
+            duration_as_fp_seconds(duration),
+            max
+        )
+    }
 }
 
 /// A tag for an interval histogram.
@@ -560,6 +644,15 @@ impl<'a> IntervalLogHistogram<'a> {
         self.start_timestamp
     }
 
+    /// The absolute time of the start of the interval, given the log's `BaseTime`, if any.
+    ///
+    /// With a `BaseTime`, the start timestamp is relative to it; without one, the start timestamp
+    /// is interpreted as seconds since the epoch. See the module-level documentation about
+    /// timestamps.
+    pub fn absolute_start_time(&self, base_time: Option<time::SystemTime>) -> time::SystemTime {
+        base_time.unwrap_or(time::UNIX_EPOCH) + self.start_timestamp
+    }
+
     /// Duration of the interval in seconds.
     pub fn duration(&self) -> time::Duration {
         self.duration
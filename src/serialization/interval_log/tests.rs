@@ -128,6 +128,67 @@ fn write_headers_multiple_times_only_last_is_used() {
     assert_eq!(expected, str::from_utf8(&buf[..]).unwrap());
 }
 
+#[test]
+fn write_legend_after_headers_then_parse() {
+    let mut buf = Vec::new();
+    let mut serializer = V2Serializer::new();
+
+    let h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
+
+    {
+        let mut log_writer = IntervalLogWriterBuilder::new()
+            .add_comment("c")
+            .with_start_time(system_time_after_epoch(100, 0))
+            .with_legend(true)
+            .begin_log_with(&mut buf, &mut serializer)
+            .unwrap();
+
+        log_writer
+            .write_histogram(
+                &h,
+                time::Duration::new(1, 234_000_000),
+                time::Duration::new(5, 678_000_000),
+                None,
+            )
+            .unwrap();
+    }
+
+    let expected = "\
+                    #c\n\
+                    #[StartTime: 100.000 (seconds since epoch)]\n\
+                    \"StartTimestamp\",\"Interval_Length\",\"Interval_Max\",\"Interval_Compressed_Histogram\"\n\
+                    1.234,5.678,0.000,HISTEwAAAAEAAAAAAAAAAwAAAAAAAAAB//////////8/8AAAAAAAAAA=\n";
+    assert_eq!(expected, str::from_utf8(&buf[..]).unwrap());
+
+    let entries = IntervalLogIterator::new(&buf)
+        .map(|r| r.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(2, entries.len());
+    assert_eq!(LogEntry::StartTime(time::Duration::new(100, 0)), entries[0]);
+    match &entries[1] {
+        LogEntry::Interval(ilh) => {
+            assert_eq!(time::Duration::new(1, 234_000_000), ilh.start_timestamp())
+        }
+        _ => panic!("expected an interval"),
+    }
+}
+
+#[test]
+fn write_legend_disabled() {
+    let mut buf = Vec::new();
+    let mut serializer = V2Serializer::new();
+
+    {
+        let _ = IntervalLogWriterBuilder::new()
+            .with_legend(true)
+            .with_legend(false)
+            .begin_log_with(&mut buf, &mut serializer)
+            .unwrap();
+    }
+
+    assert_eq!("", str::from_utf8(&buf[..]).unwrap());
+}
+
 #[test]
 fn write_interval_histo_no_tag() {
     let mut buf = Vec::new();
@@ -187,6 +248,48 @@ fn write_interval_histo_with_tag() {
     );
 }
 
+#[test]
+fn write_raw_interval_parses_back_with_same_encoded_histogram() {
+    let mut buf = Vec::new();
+    let mut serializer = V2Serializer::new();
+    let encoded = "HISTEwAAAAEAAAAAAAAAAwAAAAAAAAAB//////////8/8AAAAAAAAAA=";
+
+    {
+        let mut log_writer = IntervalLogWriterBuilder::new()
+            .with_max_value_divisor(1000.0)
+            .begin_log_with(&mut buf, &mut serializer)
+            .unwrap();
+
+        log_writer
+            .write_raw_interval(
+                time::Duration::new(1, 234_000_000),
+                time::Duration::new(5, 678_000_000),
+                9.1,
+                Tag::new("t"),
+                encoded,
+            )
+            .unwrap();
+    }
+
+    let entries = IntervalLogIterator::new(&buf)
+        .map(|r| r.unwrap())
+        .filter_map(|e| match e {
+            LogEntry::Interval(ilh) => Some(ilh),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let expected = IntervalLogHistogram {
+        tag: Some(Tag("t")),
+        start_timestamp: time::Duration::new(1, 234_000_000),
+        duration: time::Duration::new(5, 678_000_000),
+        // not scaled by the max value divisor
+        max: 9.1,
+        encoded_histogram: encoded,
+    };
+    assert_eq!(vec![expected], entries);
+}
+
 #[test]
 fn write_start_time() {
     let mut buf = Vec::new();
@@ -365,6 +468,34 @@ fn parse_interval_hist_with_tag() {
     assert_eq!(b"foo", rest);
 }
 
+#[test]
+fn absolute_start_time_with_base_time() {
+    let (_, e) = interval_hist(b"0.127,1.007,2.769,couldBeBase64\n").unwrap();
+    let ilh = match e {
+        LogEntry::Interval(ilh) => ilh,
+        _ => panic!("not an interval"),
+    };
+
+    assert_eq!(
+        system_time_after_epoch(1_500_000_040, 127_000_000),
+        ilh.absolute_start_time(Some(system_time_after_epoch(1_500_000_040, 0)))
+    );
+}
+
+#[test]
+fn absolute_start_time_without_base_time() {
+    let (_, e) = interval_hist(b"1500000040.127,1.007,2.769,couldBeBase64\n").unwrap();
+    let ilh = match e {
+        LogEntry::Interval(ilh) => ilh,
+        _ => panic!("not an interval"),
+    };
+
+    assert_eq!(
+        system_time_after_epoch(1_500_000_040, 127_000_000),
+        ilh.absolute_start_time(None)
+    );
+}
+
 #[test]
 fn iter_with_ignored_prefix() {
     let mut data = Vec::new();
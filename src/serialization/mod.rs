@@ -49,6 +49,18 @@
 //! `Read`. This should make it easy to use them in almost any context, as everything from i/o
 //! streams to `Vec<u8>` can be a `Read` or `Write`.
 //!
+//! Deserializing always produces an owned `Histogram`. The V2 formats store counts as
+//! variable-length zig-zag encoded integers with runs of zeros collapsed, so there is no
+//! fixed-width counts array in the encoded bytes that could be queried in place (e.g. from a
+//! memory-mapped file). To query many archived histograms cheaply, re-use one `Deserializer` so
+//! that its internal buffer is amortized.
+//!
+//! Histogram tags are not part of the V2 formats: the Java implementation defines no tagged
+//! cookie, and a `Histogram` has no tag field to round-trip. Tags are carried alongside the
+//! serialized histogram in interval logs instead (see `IntervalLogWriter::write_histogram` and
+//! `IntervalLogHistogram::tag`), which keeps the encoded bytes readable by other HdrHistogram
+//! implementations.
+//!
 //! # Interval logs
 //!
 //! See the `interval_log` module.
@@ -177,6 +189,9 @@
 //! assert_eq!(num_histograms * 7, accumulator.count_at(42));
 //! ```
 //!
+//! If you don't know how many histograms there are, `Deserializer::deserialize_all` will keep
+//! reading them until EOF.
+//!
 
 use std::{fmt, io};
 
@@ -195,7 +210,9 @@ mod v2_deflate_serializer;
 pub use self::v2_deflate_serializer::{V2DeflateSerializeError, V2DeflateSerializer};
 
 mod deserializer;
-pub use self::deserializer::{DeserializeError, Deserializer};
+pub use self::deserializer::{
+    Base64DeserializeError, DeserializeAllIter, DeserializeError, Deserializer,
+};
 
 pub mod interval_log;
 
@@ -224,3 +241,27 @@ pub trait Serializer {
         writer: &mut W,
     ) -> Result<usize, Self::SerializeError>;
 }
+
+/// Serialize each of several histograms in the V2 format, in parallel on rayon's thread pool.
+///
+/// Each worker uses its own `V2Serializer`, since a serializer reuses its internal buffer and so
+/// can't be shared. The serialized bytes are returned in the same order as `histograms`, and are
+/// the same as serializing each histogram in turn would produce.
+///
+/// Only available with the `rayon` feature. Returns the first error encountered, if any; see
+/// `V2SerializeError`.
+#[cfg(feature = "rayon")]
+pub fn serialize_many_parallel<T: Counter + Sync>(
+    histograms: &[Histogram<T>],
+) -> Result<Vec<Vec<u8>>, V2SerializeError> {
+    use rayon::prelude::*;
+
+    histograms
+        .par_iter()
+        .map_init(V2Serializer::new, |serializer, h| {
+            let mut buf = Vec::new();
+            let _ = serializer.serialize(h, &mut buf)?;
+            Ok(buf)
+        })
+        .collect()
+}
@@ -271,6 +271,58 @@ fn encode_counts_first_and_last_count_incremented() {
     assert_eq!(4, cursor.position());
 }
 
+#[test]
+fn encode_counts_only_high_buckets() {
+    let mut h = histo64(1, u64::max_value(), 3);
+    let mut vec = vec![0; counts_array_max_encoded_size(h.counts.len()).unwrap()];
+
+    let low = 1 << 60;
+    let high = (1 << 61) + (1 << 55);
+    h.record_n(low, 3).unwrap();
+    h.record(high).unwrap();
+    let low_index = h.index_for(low).unwrap();
+    let high_index = h.index_for(high).unwrap();
+
+    let encoded_len = encode_counts(&h, &mut vec[..]).unwrap();
+
+    // the leading zeros are one run, same as any other zero run
+    let mut cursor = Cursor::new(&vec[..encoded_len]);
+    assert_eq!(
+        -(low_index as i64),
+        zig_zag_decode(varint_read(&mut cursor).unwrap())
+    );
+    assert_eq!(3, zig_zag_decode(varint_read(&mut cursor).unwrap()));
+    assert_eq!(
+        -((high_index - low_index - 1) as i64),
+        zig_zag_decode(varint_read(&mut cursor).unwrap())
+    );
+    assert_eq!(1, zig_zag_decode(varint_read(&mut cursor).unwrap()));
+    assert_eq!(encoded_len as u64, cursor.position());
+
+    let mut serialized = Vec::new();
+    let bytes_written = V2Serializer::new().serialize(&h, &mut serialized).unwrap();
+    assert_eq!(V2_HEADER_SIZE + encoded_len, bytes_written);
+    let h2: Histogram<u64> = Deserializer::new()
+        .deserialize(&mut serialized.as_slice())
+        .unwrap();
+    assert_deserialized_histogram_matches_orig(h, h2);
+}
+
+#[test]
+fn encode_counts_single_leading_zero() {
+    let mut h = histo64(1, 2047, 3);
+    let mut vec = vec![0; counts_array_max_encoded_size(h.counts.len()).unwrap()];
+
+    h.record(1).unwrap();
+    let encoded_len = encode_counts(&h, &mut vec[..]).unwrap();
+    assert_eq!(2, encoded_len);
+
+    let mut cursor = Cursor::new(vec);
+    // a single zero is written as a 0 count rather than a run
+    assert_eq!(0, zig_zag_decode(varint_read(&mut cursor).unwrap()));
+    assert_eq!(1, zig_zag_decode(varint_read(&mut cursor).unwrap()));
+}
+
 #[test]
 fn encode_counts_count_too_big() {
     let mut h = histo64(1, 2047, 3);
@@ -13,6 +13,8 @@ pub enum V2SerializeError {
     /// Internal calculations cannot be represented in `usize`. Use smaller histograms or beefier
     /// hardware.
     UsizeTypeTooSmall,
+    /// The provided buffer is too small to hold the serialized histogram.
+    BufferTooSmall,
     /// An i/o operation failed.
     IoError(io::Error),
 }
@@ -33,6 +35,10 @@ impl fmt::Display for V2SerializeError {
             V2SerializeError::UsizeTypeTooSmall => {
                 write!(f, "Internal calculations cannot be represented in `usize`")
             }
+            V2SerializeError::BufferTooSmall => write!(
+                f,
+                "The provided buffer is too small to hold the serialized histogram"
+            ),
             V2SerializeError::IoError(e) => write!(f, "An i/o operation failed: {}", e),
         }
     }
@@ -62,6 +68,39 @@ impl V2Serializer {
     pub fn new() -> V2Serializer {
         V2Serializer { buf: Vec::new() }
     }
+
+    /// Compute an upper bound on the number of bytes that serializing `h` will produce, without
+    /// actually encoding it. This is useful for sizing a buffer up front.
+    ///
+    /// Returns `None` if the size cannot be represented in `usize`.
+    pub fn max_encoded_len<T: Counter>(h: &Histogram<T>) -> Option<usize> {
+        counts_array_max_encoded_size(h.counts.len()).and_then(|x| x.checked_add(V2_HEADER_SIZE))
+    }
+
+    /// Serialize the histogram into the provided slice rather than a `Write`.
+    ///
+    /// Returns the number of bytes written to the start of `buf`, or
+    /// `V2SerializeError::BufferTooSmall` if the serialized histogram does not fit. If `buf` is at
+    /// least `max_encoded_len(h)` long, the histogram is encoded directly into it without using
+    /// any intermediate buffer.
+    pub fn serialize_to_slice<T: Counter>(
+        &mut self,
+        h: &Histogram<T>,
+        buf: &mut [u8],
+    ) -> Result<usize, V2SerializeError> {
+        let max_size = max_encoded_size(h).ok_or(V2SerializeError::UsizeTypeTooSmall)?;
+        if buf.len() >= max_size {
+            // room for the worst case, so encode in place
+            return encode(h, buf);
+        }
+
+        // the encoded histogram may still fit, but we need scratch space to find out
+        let len = self.serialize(h, &mut io::sink())?;
+        buf.get_mut(..len)
+            .ok_or(V2SerializeError::BufferTooSmall)?
+            .copy_from_slice(&self.buf[..len]);
+        Ok(len)
+    }
 }
 
 impl Serializer for V2Serializer {
@@ -76,42 +115,46 @@ impl Serializer for V2Serializer {
 
         self.buf.clear();
         let max_size = max_encoded_size(h).ok_or(V2SerializeError::UsizeTypeTooSmall)?;
-        self.buf.reserve(max_size);
+        // encode() treats the buffer as a slice, so it must be initialized
+        self.buf.resize(max_size, 0);
+
+        let total_len = encode(h, &mut self.buf)?;
+
+        writer
+            .write_all(&self.buf[0..(total_len)])
+            .map(|_| total_len)
+            .map_err(V2SerializeError::IoError)
+    }
+}
 
-        self.buf.write_u32::<BigEndian>(V2_COOKIE)?;
+/// Encode the header and counts into the slice, which must be at least `max_encoded_size(h)` long.
+/// Returns the number of bytes written.
+fn encode<T: Counter>(h: &Histogram<T>, buf: &mut [u8]) -> Result<usize, V2SerializeError> {
+    {
+        let mut header = &mut buf[..V2_HEADER_SIZE];
+        header.write_u32::<BigEndian>(V2_COOKIE)?;
         // placeholder for length
-        self.buf.write_u32::<BigEndian>(0)?;
+        header.write_u32::<BigEndian>(0)?;
         // normalizing index offset
-        self.buf.write_u32::<BigEndian>(0)?;
-        self.buf
-            .write_u32::<BigEndian>(u32::from(h.significant_value_digits))?;
-        self.buf
-            .write_u64::<BigEndian>(h.lowest_discernible_value)?;
-        self.buf.write_u64::<BigEndian>(h.highest_trackable_value)?;
+        header.write_u32::<BigEndian>(0)?;
+        header.write_u32::<BigEndian>(u32::from(h.significant_value_digits))?;
+        header.write_u64::<BigEndian>(h.lowest_discernible_value)?;
+        header.write_u64::<BigEndian>(h.highest_trackable_value)?;
         // int to double conversion
-        self.buf.write_f64::<BigEndian>(1.0)?;
+        header.write_f64::<BigEndian>(1.0)?;
 
-        debug_assert_eq!(V2_HEADER_SIZE, self.buf.len());
-
-        unsafe {
-            // want to treat the rest of the vec as a slice, and we've already reserved this
-            // space, so this way we don't have to resize() on a lot of dummy bytes.
-            self.buf.set_len(max_size);
-        }
+        debug_assert!(header.is_empty());
+    }
 
-        let counts_len = encode_counts(h, &mut self.buf[V2_HEADER_SIZE..])?;
-        // addition should be safe as max_size is already a usize
-        let total_len = V2_HEADER_SIZE + counts_len;
+    let counts_len = encode_counts(h, &mut buf[V2_HEADER_SIZE..])?;
+    // addition should be safe as the slice length is already a usize
+    let total_len = V2_HEADER_SIZE + counts_len;
 
-        // TODO benchmark fastest buffer management scheme
-        // counts is always under 2^24
-        (&mut self.buf[4..8]).write_u32::<BigEndian>(counts_len as u32)?;
+    // TODO benchmark fastest buffer management scheme
+    // counts is always under 2^24
+    (&mut buf[4..8]).write_u32::<BigEndian>(counts_len as u32)?;
 
-        writer
-            .write_all(&self.buf[0..(total_len)])
-            .map(|_| total_len)
-            .map_err(V2SerializeError::IoError)
-    }
+    Ok(total_len)
 }
 
 fn max_encoded_size<T: Counter>(h: &Histogram<T>) -> Option<usize> {
@@ -144,6 +187,25 @@ pub fn encode_counts<T: Counter>(
 
     assert!(index_limit <= h.counts.len());
 
+    // Everything before the min non-zero index is zero, so jump straight past it rather than
+    // scanning for the end of the leading zero run. The encoding is the same either way: the
+    // format has to start at index 0, so the leading zeros are still written as one run.
+    if let Some(min_index) = h.min_nonzero_index().filter(|&i| i > 0 && i <= index_limit) {
+        index = min_index;
+        // in case the min is stale and there are more zeros
+        while (index <= index_limit) && (unsafe { *(h.counts.get_unchecked(index)) } == T::zero()) {
+            index += 1;
+        }
+
+        let count_or_zeros: i64 = if index > 1 {
+            // see below about the size of zero runs
+            -(index as i64)
+        } else {
+            0
+        };
+        bytes_written += varint_write(zig_zag_encode(count_or_zeros), &mut buf[bytes_written..]);
+    }
+
     while index <= index_limit {
         // index is inside h.counts because of the assert above
         let count = unsafe { *(h.counts.get_unchecked(index)) };
@@ -204,6 +204,25 @@ impl<C: Counter> Recorder<C> {
         self.last_phase = phase;
     }
 
+    /// Send the samples recorded so far to the associated [`SyncHistogram`] without waiting for
+    /// it to initiate a phase shift.
+    ///
+    /// The samples will be incorporated by the next call to [`SyncHistogram::refresh`]. This is
+    /// useful if the recorder is about to stop recording for a while, but should still take part
+    /// in phase shifts (otherwise see [`Recorder::idle`]).
+    pub fn flush(&mut self) {
+        // hold the lock so that a concurrent refresh either drains this histogram before it
+        // shifts phase, or has already shifted phase so that this counts as our response to it
+        let _truth = self.shared.truth.lock().unwrap();
+        let phase = self.shared.phase.load(atomic::Ordering::Acquire);
+
+        // can't call self.update() due to borrow of self.shared above
+        let h = Histogram::new_from(&self.local);
+        let h = std::mem::replace(&mut self.local, h);
+        let _ = self.shared.sender.send(h).is_ok(); // if this is err, the reader went away
+        self.last_phase = phase;
+    }
+
     /// Call this method if the Recorder will be idle for a while.
     ///
     /// Until the returned guard is dropped, the associated [`SyncHistogram`] will not wait for
@@ -303,20 +322,24 @@ impl<C: Counter> SyncHistogram<C> {
     fn refresh_inner(&mut self, timeout: Option<time::Duration>) {
         let end = timeout.map(|dur| time::Instant::now() + dur);
 
-        // time to start a phase change
-        // we first want to drain any histograms left over by dropped recorders
-        // note that we do this _before_ incrementing the phase, so we know they're "old"
-        while let Ok(h) = self.receiver.try_recv() {
-            self.merged
-                .add(&h)
-                .expect("TODO: failed to merge histogram");
-        }
+        let recorders = {
+            // make sure no recorders can join, leave, or flush in the middle of this
+            let truth = self.shared.truth.lock().unwrap();
+
+            // time to start a phase change
+            // we first want to drain any histograms left over by dropped or flushed recorders
+            // note that we do this _before_ incrementing the phase, so we know they're "old"
+            while let Ok(h) = self.receiver.try_recv() {
+                self.merged
+                    .add(&h)
+                    .expect("TODO: failed to merge histogram");
+            }
 
-        // make sure no recorders can join or leave in the middle of this
-        let recorders = self.shared.truth.lock().unwrap().recorders;
+            // then, we tell writers to phase
+            let _ = self.shared.phase.fetch_add(1, atomic::Ordering::AcqRel);
 
-        // then, we tell writers to phase
-        let _ = self.shared.phase.fetch_add(1, atomic::Ordering::AcqRel);
+            truth.recorders
+        };
 
         // we want to wait for writers to all have phased
         let mut phased = 0;
@@ -366,6 +389,19 @@ impl<C: Counter> SyncHistogram<C> {
         self.refresh_inner(Some(timeout))
     }
 
+    /// Record `value` directly into this histogram, rather than through a [`Recorder`].
+    ///
+    /// The sample is visible immediately, without a [`SyncHistogram::refresh`]. Since this
+    /// requires `&mut self`, it cannot race with a refresh, and recorders are never affected: their
+    /// samples are merged in alongside this one on the next refresh. This is handy for recording
+    /// from the thread that owns the `SyncHistogram`, e.g. during single-threaded setup before any
+    /// recorders are handed out. Other threads should use a [`Recorder`].
+    ///
+    /// Returns an error if `value` cannot be recorded; see `RecordError`.
+    pub fn record(&mut self, value: u64) -> Result<(), RecordError> {
+        self.merged.record(value)
+    }
+
     /// Obtain another multi-threaded writer for this histogram.
     ///
     /// Note that writes made to the `Recorder` will not be visible until the next call to
@@ -399,3 +399,83 @@ fn index_for_way_past_largest_value_exceeds_length() {
     // Start index is (bucket index + 1) * 1024.
     assert_eq!(1024 * (30 + 1), h.index_for(1 << 40).unwrap());
 }
+
+#[test]
+fn nonzero_index_bounds() {
+    let mut h = histo64(1, 1_u64 << 32, 3);
+    assert_eq!(None, h.max_nonzero_index());
+    assert_eq!(None, h.min_nonzero_index());
+
+    h += 1000;
+    assert_eq!(h.index_for(1000), h.max_nonzero_index());
+    assert_eq!(h.index_for(1000), h.min_nonzero_index());
+
+    h += 5_000_000;
+    h += 3;
+    assert_eq!(h.index_for(5_000_000), h.max_nonzero_index());
+    assert_eq!(h.index_for(3), h.min_nonzero_index());
+
+    h += 0;
+    assert_eq!(Some(0), h.min_nonzero_index());
+
+    h.reset();
+    assert_eq!(None, h.max_nonzero_index());
+    assert_eq!(None, h.min_nonzero_index());
+}
+
+#[test]
+fn nonzero_index_bounds_unit_magnitude_12() {
+    let mut h = histo64(1_u64 << 12, 1_u64 << 32, 3);
+    assert_eq!(12, h.unit_magnitude);
+
+    // below low, but still in the lowest bucket
+    h += 5;
+    assert_eq!(Some(0), h.min_nonzero_index());
+    assert_eq!(Some(0), h.max_nonzero_index());
+
+    h += 100_000;
+    assert_eq!(h.index_for(100_000), h.max_nonzero_index());
+
+    let mut low = histo64(1_u64 << 12, 1_u64 << 32, 3);
+    low += 5;
+    h.subtract(&low).unwrap();
+    assert_eq!(h.index_for(100_000), h.min_nonzero_index());
+}
+
+#[test]
+fn record_returning_index_in_range() {
+    let mut h = histo64(1, 1 << 32, 3);
+
+    for &v in &[0, 1, 1000, 2047, 2048, 100_000, 1 << 32] {
+        let index = h.record_returning_index(v).unwrap();
+        assert_eq!(h.index_for(v), Some(index));
+        assert_eq!(1, h.counts[index]);
+    }
+    assert_eq!(7, h.len());
+}
+
+#[test]
+fn record_returning_index_clamped_and_resized() {
+    let mut clamped = histo64(1000, 100_000, 3);
+    clamped.on_out_of_range(crate::OutOfRangePolicy::Clamp);
+
+    let high = clamped.record_returning_index(u64::max_value()).unwrap();
+    assert_eq!(clamped.index_for(100_000), Some(high));
+    // values below the lowest discernible value share the first bucket rather than moving
+    let low = clamped.record_returning_index(1).unwrap();
+    assert_eq!(Some(low), clamped.index_for(1));
+    assert_eq!(0, low);
+    assert_eq!(1, clamped.counts[high]);
+    assert_eq!(1, clamped.counts[low]);
+
+    let mut resized = histo64(1, 2048, 3);
+    resized.auto(true);
+    let index = resized.record_returning_index(1 << 40).unwrap();
+    assert!(index >= 2048);
+    assert_eq!(resized.index_for(1 << 40), Some(index));
+    assert_eq!(1, resized.counts[index]);
+
+    let mut fixed = histo64(1, 2048, 3);
+    assert!(fixed.record_returning_index(1 << 40).is_err());
+    assert!(fixed.is_empty());
+}
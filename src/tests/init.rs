@@ -1,4 +1,5 @@
 use crate::tests::helpers::histo64;
+use crate::Histogram;
 
 #[test]
 fn init_fields_smallest_possible_array() {
@@ -268,3 +269,23 @@ fn init_fields_max_value_max_unit_magnitude_max_precision() {
 
     assert_eq!(64 - 62 - 1, h.leading_zero_count_base);
 }
+
+#[test]
+fn new_auto_with_capacity_does_not_reallocate_up_to_expected_max() {
+    let expected_max = 3_600_000_000;
+    let mut h = Histogram::<u64>::new_auto_with_capacity(3, expected_max).unwrap();
+    let capacity = h.counts.capacity();
+
+    let mut v = 1;
+    while v < expected_max {
+        h.record(v).unwrap();
+        v *= 3;
+    }
+    h.record(expected_max).unwrap();
+    assert_eq!(capacity, h.counts.capacity());
+
+    // still able to grow past the expected max
+    h.record(expected_max * 1000).unwrap();
+    assert!(h.counts.capacity() > capacity);
+    assert!(h.high() >= expected_max * 1000);
+}
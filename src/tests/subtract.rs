@@ -120,6 +120,42 @@ fn subtract_subtrahend_values_outside_minuend_range_error() {
     assert_min_max_count(big);
 }
 
+#[test]
+fn subtract_subtrahend_values_outside_minuend_range_auto_resizes() {
+    let mut h1 = Histogram::<u64>::new(3).unwrap();
+    h1 += TEST_VALUE_LEVEL;
+    h1 += 1000 * TEST_VALUE_LEVEL;
+    let original_high = h1.high();
+
+    let max = u64::max_value() / 2;
+    let mut big = Histogram::<u64>::new_with_max(max, 3).unwrap();
+    big += TEST_VALUE_LEVEL;
+    big += max;
+    assert!(original_high < max);
+
+    // resizing works, but there's nothing to subtract from in the new range
+    assert_eq!(
+        SubtractionError::SubtrahendCountExceedsMinuendCount,
+        h1.subtract(&big).unwrap_err()
+    );
+    assert!(h1.high() >= max);
+    assert_eq!(h1.counts.len(), h1.index_for(max).unwrap() + 1);
+    // counts in the original range were subtracted before the error
+    assert_eq!(0, h1.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(1, h1.count_at(1000 * TEST_VALUE_LEVEL));
+    assert_eq!(0, h1.count_at(max));
+
+    // once the minuend has those values, subtracting works
+    h1 += TEST_VALUE_LEVEL;
+    h1 += max;
+    h1.subtract(&big).unwrap();
+    assert_eq!(1, h1.len());
+    assert_eq!(1, h1.count_at(1000 * TEST_VALUE_LEVEL));
+
+    assert_min_max_count(h1);
+    assert_min_max_count(big);
+}
+
 #[test]
 fn subtract_values_inside_minuend_range_works() {
     let max = u64::max_value() / 2;
@@ -372,3 +408,243 @@ fn subtract_values_minuend_saturated_total_recalculates_not_saturated() {
     assert_min_max_count(h1);
     assert_min_max_count(h2);
 }
+
+#[test]
+fn subtract_values_total_count_underflow_recalculates() {
+    let mut h1 = Histogram::<u64>::new_with_max(u64::MAX, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(u64::MAX, 3).unwrap();
+
+    let chunk = u64::MAX / 4;
+
+    h1.record_n(1, chunk).unwrap();
+    h1.record_n(10, chunk).unwrap();
+    h1.record_n(100, chunk).unwrap();
+    // total_count no longer agrees with the counts, as can happen when bucket counts saturate
+    h1.total_count = 1;
+
+    h2.record_n(10, chunk).unwrap();
+
+    // would underflow total_count, so must restat rather than panic
+    h1.subtract(&h2).unwrap();
+
+    assert_eq!(1, h1.min());
+    assert_eq!(100, h1.max());
+    assert_eq!(chunk * 2, h1.len());
+
+    assert_min_max_count(h1);
+    assert_min_max_count(h2);
+}
+
+#[test]
+fn subtract_different_sigfig() {
+    let mut h1 = Histogram::<u64>::new_with_max(1_000_000, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(1_000_000, 2).unwrap();
+
+    // both values are the lowest equivalent value of their bucket at either precision
+    for &v in &[128, 8192] {
+        h1 += v;
+        h2 += v;
+    }
+    h1 += 5_000;
+
+    h1.subtract(&h2).unwrap();
+    assert_eq!(h1.count_at(128), 0);
+    assert_eq!(h1.count_at(8192), 0);
+    assert_eq!(h1.count_at(5_000), 1);
+    assert_eq!(h1.len(), 1);
+
+    assert_min_max_count(h1);
+}
+
+#[test]
+fn subtract_different_sigfig_coarser_subtrahend_errors() {
+    let mut h1 = Histogram::<u64>::new_with_max(1_000_000, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(1_000_000, 2).unwrap();
+
+    // lands in the same bucket as 8192 in h2, but not in h1
+    h1 += 8200;
+    h2 += 8200;
+
+    assert_eq!(
+        SubtractionError::SubtrahendCountExceedsMinuendCount,
+        h1.subtract(&h2).unwrap_err()
+    );
+}
+
+#[test]
+fn subtract_different_low() {
+    let mut h1 = Histogram::<u64>::new_with_bounds(1, 1_000_000, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_bounds(1024, 1_000_000, 3).unwrap();
+
+    h1 += 2048;
+    h1 += 4096;
+    h1 += 3;
+    h2 += 2048;
+    h2 += 4096;
+
+    h1.subtract(&h2).unwrap();
+    assert_eq!(h1.count_at(2048), 0);
+    assert_eq!(h1.count_at(4096), 0);
+    assert_eq!(h1.count_at(3), 1);
+    assert_eq!(h1.len(), 1);
+
+    assert_min_max_count(h1);
+}
+
+#[test]
+fn subtracted_matches_clone_and_subtract() {
+    let mut h1 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+
+    h1.record_n(TEST_VALUE_LEVEL, 3).unwrap();
+    h1.record_n(1000 * TEST_VALUE_LEVEL, 2).unwrap();
+    h2 += TEST_VALUE_LEVEL;
+    h2 += 1000 * TEST_VALUE_LEVEL;
+    let original = h1.clone();
+
+    let mut expected = h1.clone();
+    expected.subtract(&h2).unwrap();
+
+    let difference = h1.subtracted(&h2).unwrap();
+    assert_eq!(expected, difference);
+    assert_eq!(difference.count_at(TEST_VALUE_LEVEL), 2);
+    assert_eq!(difference.count_at(1000 * TEST_VALUE_LEVEL), 1);
+    assert_min_max_count(&difference);
+
+    // the minuend is untouched
+    assert_eq!(original, h1);
+    assert_eq!(h1.len(), 5);
+}
+
+#[test]
+fn subtracted_error_leaves_minuend_unchanged() {
+    let mut h1 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+
+    h1 += TEST_VALUE_LEVEL;
+    h2.record_n(TEST_VALUE_LEVEL, 2).unwrap();
+    let original = h1.clone();
+
+    assert_eq!(
+        SubtractionError::SubtrahendCountExceedsMinuendCount,
+        h1.subtracted(&h2).unwrap_err()
+    );
+    assert_eq!(original, h1);
+}
+
+#[test]
+fn subtract_sparse_wide_restat_only_scans_to_max() {
+    let mut h1 = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 5).unwrap();
+    let mut h2 = Histogram::<u64>::new_from(&h1);
+
+    h1.record_n(1, 2).unwrap();
+    h1 += 1_000;
+    h1 += 1_000_000;
+    h2 += 1;
+    h2 += 1_000_000;
+
+    // subtracting the min and max forces a restat
+    h1.subtract(&h2).unwrap();
+    assert_eq!(1, h1.min());
+    assert_eq!(h1.highest_equivalent(1_000), h1.max());
+    assert_eq!(2, h1.len());
+    assert_min_max_count(&h1);
+
+    h1.subtract(h1.clone()).unwrap();
+    assert_eq!(0, h1.len());
+    assert_min_max_count(&h1);
+}
+
+#[test]
+fn scale_counts_restat_only_scans_to_max() {
+    let mut h = Histogram::<u8>::new_with_bounds(1, u64::max_value(), 3).unwrap();
+    h += 3;
+    h.record_n(1 << 40, 2).unwrap();
+
+    h.scale_counts(100);
+    assert_eq!(3, h.min());
+    assert_eq!(h.highest_equivalent(1 << 40), h.max());
+    assert_eq!(100 + 200, h.len());
+    assert_min_max_count(&h);
+}
+
+#[test]
+fn len_to_max_is_capped_at_counts_len() {
+    let mut h = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    h += 3;
+    // a tracked max past the end of the counts array, as record_pairs_bulk used to produce
+    h.max_value = u64::MAX;
+
+    assert_eq!(h.distinct_values(), h.len_to_max());
+    let mut seen = 0;
+    h.for_each_recorded(|_, _, _| seen += 1);
+    assert_eq!(1, seen);
+    h.scale_counts(2);
+    assert_eq!(h.highest_equivalent(3), h.max());
+    assert_min_max_count(&h);
+}
+
+#[test]
+fn subtract_collecting_errors_reports_all_conflicts() {
+    let mut h1 = Histogram::<u64>::new_with_max(100_000, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(100_000, 3).unwrap();
+
+    h1.record_n(10, 5).unwrap();
+    h1.record_n(20, 1).unwrap();
+    h1.record_n(1000, 7).unwrap();
+    h1.record_n(5000, 2).unwrap();
+
+    h2.record_n(10, 3).unwrap();
+    h2.record_n(20, 4).unwrap();
+    h2.record_n(1000, 7).unwrap();
+    h2.record_n(3000, 1).unwrap();
+    h2.record_n(5000, 9).unwrap();
+
+    assert_eq!(
+        vec![(20, 1, 4), (3000, 0, 1), (5000, 2, 9)],
+        h1.subtract_collecting_errors(&h2).unwrap_err()
+    );
+
+    assert_eq!(2, h1.count_at(10));
+    assert_eq!(0, h1.count_at(20));
+    assert_eq!(0, h1.count_at(1000));
+    assert_eq!(0, h1.count_at(3000));
+    assert_eq!(0, h1.count_at(5000));
+    assert_min_max_count(&h1);
+}
+
+#[test]
+fn subtract_collecting_errors_reports_out_of_range_values() {
+    let mut h1 = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(100_000, 3).unwrap();
+
+    h1.record_n(10, 5).unwrap();
+    h2.record_n(10, 1).unwrap();
+    h2.record_n(50_000, 2).unwrap();
+
+    let value = h2.lowest_equivalent(50_000);
+    assert_eq!(
+        vec![(value, 0, 2)],
+        h1.subtract_collecting_errors(&h2).unwrap_err()
+    );
+    assert_eq!(4, h1.count_at(10));
+    assert_min_max_count(&h1);
+}
+
+#[test]
+fn subtract_collecting_errors_without_conflicts_matches_subtract() {
+    let mut h1 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+
+    h1 += TEST_VALUE_LEVEL;
+    h1 += 1000 * TEST_VALUE_LEVEL;
+    h1 += 1000 * TEST_VALUE_LEVEL;
+    h2 += 1000 * TEST_VALUE_LEVEL;
+
+    let mut expected = h1.clone();
+    expected.subtract(&h2).unwrap();
+    h1.subtract_collecting_errors(&h2).unwrap();
+
+    assert_eq!(expected, h1);
+    assert_min_max_count(&h1);
+}
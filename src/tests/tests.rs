@@ -14,7 +14,22 @@ mod value_calculation;
 #[test]
 fn new_err_high_not_double_low() {
     let res = Histogram::<u64>::new_with_bounds(10, 15, 0);
-    assert_eq!(CreationError::HighLessThanTwiceLow, res.unwrap_err());
+    assert_eq!(
+        CreationError::HighLessThanTwiceLow { min_high: 20 },
+        res.unwrap_err()
+    );
+}
+
+#[test]
+fn new_err_high_not_double_low_reports_min_high() {
+    for &low in &[1, 3, 1000, 1 << 40] {
+        let res = Histogram::<u64>::new_with_bounds(low, 2 * low - 1, 0);
+        assert_eq!(
+            CreationError::HighLessThanTwiceLow { min_high: 2 * low },
+            res.unwrap_err()
+        );
+        assert!(Histogram::<u64>::new_with_bounds(low, 2 * low, 0).is_ok());
+    }
 }
 
 #[test]
@@ -1,6 +1,6 @@
 //! Tests from HistogramAutosizingTest.java
 
-use hdrhistogram::Histogram;
+use hdrhistogram::{AdditionError, Histogram};
 
 #[test]
 fn histogram_autosizing_edges() {
@@ -40,6 +40,29 @@ fn autosizing_add() {
     assert!(histogram2.equivalent(histogram2.max(), 1000000000_u64));
 }
 
+#[test]
+fn add_reporting_resize() {
+    let mut wide = Histogram::<u64>::new(2).unwrap();
+    wide += 1000_u64;
+    wide += 1000000000_u64;
+    let mut narrow = Histogram::<u64>::new(2).unwrap();
+    narrow += 1000_u64;
+
+    let mut histogram = Histogram::<u64>::new_with_max(10_000, 2).unwrap();
+    histogram.auto(true);
+    assert!(!histogram.add_reporting_resize(&narrow).unwrap());
+    assert!(histogram.add_reporting_resize(&wide).unwrap());
+    assert!(histogram.equivalent(histogram.max(), 1000000000_u64));
+    // already wide enough now
+    assert!(!histogram.add_reporting_resize(&wide).unwrap());
+    assert!(!histogram.add_reporting_resize(&narrow).unwrap());
+    assert_eq!(6, histogram.len());
+
+    let mut fixed = Histogram::<u64>::new_with_max(10_000, 2).unwrap();
+    assert!(fixed.add_reporting_resize(&wide).is_err());
+    assert!(!fixed.add_reporting_resize(&narrow).unwrap());
+}
+
 #[test]
 fn autosizing_across_continuous_range() {
     let mut histogram = Histogram::<u64>::new(2).unwrap();
@@ -57,3 +80,162 @@ fn can_acess_autoresize_setting() {
     histogram.auto(false);
     assert!(!histogram.is_auto_resize());
 }
+
+#[test]
+fn record_checked_reports_resize() {
+    let mut histogram = Histogram::<u64>::new(3).unwrap();
+    let len = histogram.distinct_values();
+
+    assert_eq!(Ok(true), histogram.record_checked(1_000_000));
+    assert!(histogram.distinct_values() > len);
+    assert_eq!(Ok(false), histogram.record_checked(1_000));
+    assert_eq!(2, histogram.len());
+}
+
+#[test]
+fn record_checked_resize_disabled() {
+    let mut histogram = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    assert_eq!(Ok(false), histogram.record_checked(1000));
+    assert!(histogram.record_checked(1_000_000).is_err());
+}
+
+#[test]
+fn reset_and_shrink_restores_original_size() {
+    let mut histogram = Histogram::<u64>::new_with_max(3600 * 1000 * 1000, 3).unwrap();
+    let buckets = histogram.buckets();
+    let len = histogram.distinct_values();
+    let high = histogram.high();
+
+    histogram.auto(true);
+    histogram += u64::max_value();
+    assert!(histogram.distinct_values() > len);
+
+    histogram.reset_and_shrink();
+    assert_eq!(buckets, histogram.buckets());
+    assert_eq!(len, histogram.distinct_values());
+    assert_eq!(high, histogram.high());
+    assert!(histogram.is_empty());
+
+    // still usable, and can still grow
+    histogram += 1000;
+    histogram += u64::max_value();
+    assert_eq!(2, histogram.len());
+}
+
+#[test]
+fn reset_and_shrink_without_resize_keeps_size() {
+    let mut histogram = Histogram::<u64>::new_with_max(3600 * 1000 * 1000, 3).unwrap();
+    let len = histogram.distinct_values();
+    histogram += 1000;
+
+    histogram.reset_and_shrink();
+    assert_eq!(len, histogram.distinct_values());
+    assert!(histogram.is_empty());
+}
+
+#[test]
+fn add_and_subtract_empty_wider_histogram_does_not_resize() {
+    let mut histogram = Histogram::<u64>::new_with_max(1000, 3).unwrap();
+    histogram += 100;
+    let before = histogram.clone();
+    let empty = Histogram::<u64>::new_with_max(u64::max_value(), 3).unwrap();
+
+    histogram.add(&empty).unwrap();
+    histogram.subtract(&empty).unwrap();
+    assert_eq!(before.distinct_values(), histogram.distinct_values());
+    assert_eq!(before.high(), histogram.high());
+    assert_eq!(before, histogram);
+
+    // the same goes with auto-resize enabled
+    histogram.auto(true);
+    histogram.add(&empty).unwrap();
+    histogram.subtract(&empty).unwrap();
+    assert_eq!(before.distinct_values(), histogram.distinct_values());
+    assert_eq!(before.high(), histogram.high());
+}
+
+#[test]
+fn add_all_matches_sequential_add() {
+    let mut sources = Vec::new();
+    for &max in &[1_u64 << 10, 1 << 30, 1 << 20] {
+        let mut h = Histogram::<u64>::new(3).unwrap();
+        for i in 1..100 {
+            h += max / i;
+        }
+        sources.push(h);
+    }
+
+    let mut sequential = Histogram::<u64>::new(3).unwrap();
+    for h in &sources {
+        sequential.add(h).unwrap();
+    }
+    let mut all = Histogram::<u64>::new(3).unwrap();
+    all.add_all(&sources).unwrap();
+
+    assert_eq!(sequential, all);
+    assert_eq!(sequential.high(), all.high());
+    assert_eq!(sequential.distinct_values(), all.distinct_values());
+
+    let mut fixed = Histogram::<u64>::new_with_max(1 << 20, 3).unwrap();
+    assert_eq!(
+        AdditionError::OtherAddendValueExceedsRange,
+        fixed.add_all(&sources).unwrap_err()
+    );
+    assert!(fixed.is_empty());
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn add_all_resizes_at_most_once() {
+    let mut sources = Vec::new();
+    for &v in &[1_u64 << 20, 1 << 30, 1 << 40] {
+        let mut h = Histogram::<u64>::new(3).unwrap();
+        h += v;
+        sources.push(h);
+    }
+
+    let mut sequential = Histogram::<u64>::new(3).unwrap();
+    sequential += 1;
+    for h in &sources {
+        sequential.add(h).unwrap();
+    }
+    assert_eq!(3, sequential.resize_count());
+
+    let mut all = Histogram::<u64>::new(3).unwrap();
+    all += 1;
+    all.add_all(&sources).unwrap();
+    assert_eq!(1, all.resize_count());
+    assert_eq!(sequential, all);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn metrics_count_records_and_resizes() {
+    let mut fixed = Histogram::<u64>::new_with_bounds(1, 1 << 40, 3).unwrap();
+    for i in 0..10_000 {
+        fixed += i * 1000;
+    }
+    fixed.record_n(5, 3).unwrap();
+    assert!(fixed.record(1 << 50).is_err());
+    fixed.saturating_record(1 << 50);
+    assert_eq!(10_002, fixed.record_count());
+    assert_eq!(0, fixed.resize_count());
+
+    let mut auto = Histogram::<u64>::new(3).unwrap();
+    auto.record_pairs_bulk(&[(1, 1), (2, 2)]).unwrap();
+    assert_eq!(0, auto.resize_count());
+    auto += 1_u64 << 20;
+    auto += 1_u64 << 20;
+    auto += 1_u64 << 40;
+    assert_eq!(2, auto.resize_count());
+    assert_eq!(5, auto.record_count());
+
+    let mut wider = Histogram::<u64>::new(3).unwrap();
+    wider.add(&auto).unwrap();
+    assert_eq!(1, wider.resize_count());
+    assert_eq!(0, wider.record_count());
+
+    // shrinking isn't growth
+    auto.reset_and_shrink();
+    assert_eq!(2, auto.resize_count());
+}
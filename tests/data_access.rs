@@ -1,6 +1,7 @@
 //! Tests from HistogramDataAccessTest.java
 
-use hdrhistogram::Histogram;
+use hdrhistogram::{Histogram, RecordError};
+use rand::{Rng, SeedableRng};
 
 macro_rules! assert_near {
     ($a:expr, $b:expr, $tolerance:expr) => {{
@@ -191,6 +192,46 @@ fn get_stdev() {
     assert_near!(hist.stdev(), expected_std_dev, 0.001);
 }
 
+#[test]
+fn centroids_total_count() {
+    let Loaded { hist, .. } = load_histograms();
+
+    for &max in &[1, 2, 7, 100, 1000, 100_000] {
+        let centroids = hist.centroids(max);
+        assert!(centroids.len() <= max);
+        assert_eq!(
+            hist.len(),
+            centroids.iter().map(|&(_, count)| count).sum::<u64>()
+        );
+        // centroids stay in value order
+        assert!(centroids.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    assert!(hist.centroids(0).is_empty());
+    assert!(Histogram::<u64>::new(SIGFIG)
+        .unwrap()
+        .centroids(10)
+        .is_empty());
+}
+
+#[test]
+fn centroids_one_per_recorded_bucket() {
+    let Loaded { hist, .. } = load_histograms();
+
+    let expected: Vec<(f64, u64)> = hist
+        .iter_recorded()
+        .map(|v| {
+            (
+                hist.median_equivalent(v.value_iterated_to()) as f64,
+                v.count_at_value(),
+            )
+        })
+        .collect();
+
+    assert_eq!(expected, hist.centroids(expected.len()));
+    assert_eq!(expected, hist.centroids(usize::MAX));
+}
+
 #[test]
 fn quantiles() {
     let Loaded { hist, raw, .. } = load_histograms();
@@ -210,6 +251,94 @@ fn quantiles() {
     assert_near!(hist.value_at_quantile(1.0), 100000000.0, 0.001);
 }
 
+#[test]
+fn value_at_quantile_zero_is_min() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+    for h in &[&hist, &raw, &post] {
+        assert_eq!(h.min(), h.value_at_quantile(0.0));
+        assert_eq!(h.min(), h.cumulative_counts().value_at_quantile(0.0));
+    }
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(10_000, 3).unwrap();
+    h += 123_456;
+    assert_eq!(h.min(), h.value_at_quantile(0.0));
+    assert_eq!(h.min(), h.value_at_quantile(-0.0));
+    assert_eq!(h.min(), h.value_at_quantile(-0.5));
+    assert_eq!(h.min(), h.value_at_percentile(0.0));
+    let cumulative = h.cumulative_counts();
+    assert_eq!(h.min(), cumulative.value_at_quantile(0.0));
+    assert_eq!(h.min(), cumulative.value_at_quantile(-0.0));
+    assert_eq!(h.min(), cumulative.value_at_quantile(-0.5));
+
+    h += 0;
+    assert_eq!(0, h.min());
+    assert_eq!(0, h.value_at_quantile(0.0));
+    assert_eq!(0, h.value_at_quantile(-0.5));
+    assert_eq!(0, h.cumulative_counts().value_at_quantile(-0.5));
+
+    // values below the lowest discernible value share the zero bucket
+    let mut coarse = Histogram::<u64>::new_with_bounds(1000, TRACKABLE_MAX, SIGFIG).unwrap();
+    coarse += 500;
+    coarse += 5000;
+    assert_eq!(coarse.min(), coarse.value_at_quantile(0.0));
+    coarse += 0;
+    assert_eq!(coarse.min(), coarse.value_at_quantile(0.0));
+
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(empty.min(), empty.value_at_quantile(0.0));
+}
+
+#[test]
+fn value_at_fraction_matches_value_at_quantile() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    for h in &[&hist, &raw, &post] {
+        for &(n, d) in &[
+            (0, 1),
+            (1, 10),
+            (3, 10),
+            (1, 2),
+            (99, 100),
+            (999, 1000),
+            (1, 1),
+        ] {
+            assert_eq!(
+                h.value_at_quantile(n as f64 / d as f64),
+                h.value_at_fraction(n, d),
+                "{}/{}",
+                n,
+                d
+            );
+        }
+        // capped at 1
+        assert_eq!(h.value_at_quantile(1.0), h.value_at_fraction(3, 2));
+        assert_eq!(h.value_at_quantile(1.0), h.value_at_fraction(3, 0));
+    }
+
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(0, empty.value_at_fraction(1, 2));
+}
+
+#[test]
+fn value_at_fraction_avoids_float_rounding() {
+    // With a total count this large, 0.999 * total in floating point overshoots the exact count
+    // of samples at 1, landing value_at_quantile in the next bucket.
+    let total: u64 = 1_000_000_000_000_001_000;
+    let at_one = 999_000_000_000_000_999;
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(1, at_one).unwrap();
+    h.record_n(1000, total - at_one).unwrap();
+    assert_eq!(total, h.len());
+
+    assert_eq!(1, h.value_at_fraction(999, 1000));
+    assert_eq!(1000, h.value_at_quantile(0.999));
+}
+
 #[test]
 fn large_quantile() {
     let largest_value = 1000000000000_u64;
@@ -238,6 +367,75 @@ fn quantile_below_saturates() {
     assert_eq!(1.0, h.quantile_below(512));
 }
 
+#[test]
+fn quantile_and_count_below_matches_separate_queries() {
+    let Loaded { hist, raw, .. } = load_histograms();
+
+    for h in &[hist, raw] {
+        for &v in &[
+            0,
+            1,
+            999,
+            1000,
+            1001,
+            5_000_000,
+            100_000_000,
+            u64::max_value(),
+        ] {
+            assert_eq!(
+                (h.quantile_below(v), h.count_between(0, v)),
+                h.quantile_and_count_below(v),
+                "{}",
+                v
+            );
+        }
+    }
+
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!((1.0, 0), empty.quantile_and_count_below(1000));
+}
+
+#[test]
+fn permille_below_matches_rounded_percentile() {
+    let Loaded { hist, raw, .. } = load_histograms();
+    for h in &[hist, raw] {
+        for &v in &[0, 1, 1_000, 5_000, 50_000_000, 100_000_000, TRACKABLE_MAX] {
+            let permille = h.permille_below(v);
+            let (_, count) = h.quantile_and_count_below(v);
+            assert_eq!(count * 1000 / h.len(), permille);
+            // the float path rounds rather than truncating, so it may be one higher
+            let rounded = (h.percentile_below(v) * 10.0).round() as u64;
+            assert!(rounded == permille || rounded == permille + 1);
+        }
+    }
+
+    let empty = Histogram::<u64>::new(SIGFIG).unwrap();
+    assert_eq!(1000, empty.permille_below(1000));
+}
+
+#[test]
+fn permille_below_is_exact_where_float_is_not() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(1, 11).unwrap();
+    h.record_n(1_000, 989).unwrap();
+
+    // 11 / 1000 as f64 is slightly below 0.011, so truncating the float path yields 10
+    assert_eq!(11, h.permille_below(1));
+    assert_eq!(10.0, (h.percentile_below(1) * 10.0).floor());
+    assert_eq!(1000, h.permille_below(1_000));
+}
+
+#[test]
+fn quantile_and_count_below_saturates() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
+
+    for i in 0..1024 {
+        h.record_n(i, u64::max_value() - 1).unwrap();
+    }
+
+    assert_eq!((1.0, u64::max_value()), h.quantile_and_count_below(512));
+}
+
 #[test]
 fn quantile_below_value_beyond_max() {
     let mut h = Histogram::<u64>::new_with_bounds(1, 100_000, 3).unwrap();
@@ -291,6 +489,45 @@ fn count_between_saturates() {
     assert_eq!(u64::max_value(), h.count_between(100, 200));
 }
 
+#[test]
+fn count_in_index_range() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+    for h in &[&hist, &raw, &post] {
+        assert_eq!(h.len(), h.count_in_index_range(0..h.distinct_values()));
+        // clamped to valid indices
+        assert_eq!(h.len(), h.count_in_index_range(0..usize::MAX));
+    }
+
+    // in the first bucket, indices are the same as values
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(10, 1).unwrap();
+    h.record_n(11, 2).unwrap();
+    h.record_n(1000, 4).unwrap();
+
+    assert_eq!(3, h.count_in_index_range(10..12));
+    assert_eq!(2, h.count_in_index_range(11..1000));
+    assert_eq!(6, h.count_in_index_range(11..1001));
+    assert_eq!(0, h.count_in_index_range(12..1000));
+    assert_eq!(0, h.count_in_index_range(11..11));
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 1000..10;
+    assert_eq!(0, h.count_in_index_range(reversed));
+    assert_eq!(0, h.count_in_index_range(usize::MAX - 1..usize::MAX));
+}
+
+#[test]
+fn count_in_index_range_saturates() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
+
+    for i in 0..1024 {
+        h.record_n(i, u64::max_value() - 1).unwrap();
+    }
+
+    assert_eq!(u64::max_value(), h.count_in_index_range(100..200));
+}
+
 #[test]
 fn count_at() {
     let Loaded { hist, raw, .. } = load_histograms();
@@ -320,6 +557,198 @@ fn quantile_iter() {
     }
 }
 
+#[test]
+fn standard_percentiles() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    for h in &[hist, raw, post] {
+        let p = h.standard_percentiles();
+        assert_eq!(h.value_at_quantile(0.5), p.p50);
+        assert_eq!(h.value_at_quantile(0.75), p.p75);
+        assert_eq!(h.value_at_quantile(0.9), p.p90);
+        assert_eq!(h.value_at_quantile(0.95), p.p95);
+        assert_eq!(h.value_at_quantile(0.99), p.p99);
+        assert_eq!(h.value_at_quantile(0.999), p.p99_9);
+        assert_eq!(h.value_at_quantile(0.9999), p.p99_99);
+        assert_eq!(h.value_at_quantile(1.0), p.max);
+        assert_eq!(h.max(), p.max);
+    }
+}
+
+#[test]
+fn standard_percentiles_empty() {
+    let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let p = h.standard_percentiles();
+
+    assert_eq!(0, p.p50);
+    assert_eq!(0, p.max);
+}
+
+#[test]
+fn count_at_quantile() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    for h in &[&hist, &raw, &post] {
+        for &q in &[0.0, 0.1, 0.5, 0.9, 0.99, 0.999, 0.9999, 1.0, 1.5] {
+            assert_eq!(h.count_at(h.value_at_quantile(q)), h.count_at_quantile(q));
+        }
+    }
+
+    // the max is the only sample in its bucket in the raw histogram
+    assert_eq!(1, raw.count_at_quantile(1.0));
+}
+
+#[test]
+fn count_at_quantile_empty() {
+    let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    assert_eq!(0, h.count_at_quantile(0.0));
+    assert_eq!(0, h.count_at_quantile(0.5));
+    assert_eq!(0, h.count_at_quantile(1.0));
+}
+
+#[test]
+fn density_at_integrates_to_bucket_mass() {
+    let Loaded {
+        hist,
+        raw,
+        scaled_hist,
+        ..
+    } = load_histograms();
+
+    for h in &[&hist, &raw, &scaled_hist] {
+        let mut total_mass = 0.0;
+        for v in h.iter_recorded() {
+            let value = v.value_iterated_to();
+            let mass = h.density_at(value) * h.equivalent_range(value) as f64;
+            assert_near!(mass, v.count_at_value() as f64 / h.len() as f64, 1e-12);
+            total_mass += mass;
+        }
+        assert_near!(total_mass, 1.0, 1e-9);
+    }
+
+    // a wider bucket with the same count has a proportionally lower density
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h += 1_000;
+    h += 1_000_000;
+    assert_near!(
+        h.density_at(1_000) / h.density_at(1_000_000),
+        h.equivalent_range(1_000_000) as f64 / h.equivalent_range(1_000) as f64,
+        1e-12
+    );
+    assert_eq!(0.0, h.density_at(5_000));
+}
+
+#[test]
+fn density_at_empty() {
+    let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(0.0, h.density_at(0));
+    assert_eq!(0.0, h.density_at(1_000));
+}
+
+#[test]
+fn value_equivalent_in_scaled() {
+    let Loaded {
+        hist, scaled_hist, ..
+    } = load_histograms();
+
+    for &v in &[100_000_000, TRACKABLE_MAX] {
+        // both histograms have the same bucket for larger values
+        assert_eq!(hist.lowest_equivalent(v), scaled_hist.lowest_equivalent(v));
+        assert_eq!(
+            hist.highest_equivalent(v),
+            scaled_hist.highest_equivalent(v)
+        );
+        assert!(hist.value_equivalent_in(v, &scaled_hist));
+        assert!(scaled_hist.value_equivalent_in(v, &hist));
+    }
+    for &v in &[1, 1_000, 1_001, 1_000 * SCALEF] {
+        // scaled_hist has a coarser resolution for small values, so hist's bucket for them is
+        // inside scaled_hist's one, but not the other way around
+        assert!(scaled_hist.value_equivalent_in(v, &hist));
+        assert!(!hist.value_equivalent_in(v, &scaled_hist));
+    }
+    assert_ne!(
+        hist.lowest_equivalent(1_001),
+        scaled_hist.lowest_equivalent(1_001)
+    );
+
+    // only scaled_hist can track values this high
+    let v = TRACKABLE_MAX * SCALEF;
+    assert!(scaled_hist.value_equivalent_in(v, &scaled_hist));
+    assert!(!hist.value_equivalent_in(v, &scaled_hist));
+    assert!(!scaled_hist.value_equivalent_in(v, &hist));
+}
+
+#[test]
+fn quantiles_of_values() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    // out of order, with duplicates and values beyond the max
+    let values = [
+        100_000_000,
+        1_000,
+        0,
+        5_000,
+        1_000,
+        u64::MAX,
+        999,
+        50_000_000,
+        100_000_000,
+    ];
+
+    for h in &[&hist, &raw, &post] {
+        let expected = values
+            .iter()
+            .map(|&v| h.quantile_below(v))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, h.quantiles_of_values(&values));
+    }
+}
+
+#[test]
+fn quantiles_of_values_empty() {
+    let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    assert_eq!(vec![1.0, 1.0], h.quantiles_of_values(&[5, 0]));
+    assert!(h.quantiles_of_values(&[]).is_empty());
+}
+
+#[test]
+fn cumulative_counts_match_direct_queries() {
+    let Loaded {
+        hist,
+        scaled_hist,
+        raw,
+        post,
+        ..
+    } = load_histograms();
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    for h in &[&hist, &scaled_hist, &raw, &post, &empty] {
+        let cumulative = h.cumulative_counts();
+        for &q in &[0.0, 0.5, 0.9, 0.99, 0.999, 1.0, 1.5] {
+            assert_eq!(h.value_at_quantile(q), cumulative.value_at_quantile(q));
+        }
+        for &v in &[0, 1, TRACKABLE_MAX, u64::MAX] {
+            assert_eq!(h.quantile_below(v), cumulative.quantile_below(v));
+        }
+        for _ in 0..1000 {
+            let q = rng.gen_range(0.0..=1.0);
+            assert_eq!(h.value_at_quantile(q), cumulative.value_at_quantile(q));
+            let v = rng.gen_range(0..=2 * h.high());
+            assert_eq!(h.quantile_below(v), cumulative.quantile_below(v));
+        }
+    }
+}
+
 #[test]
 fn linear_iter_raw() {
     let Loaded { raw, .. } = load_histograms();
@@ -547,6 +976,202 @@ fn value_duplication() {
     );
 }
 
+#[test]
+fn record_pairs_reconstructs_histogram() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    for h in &[&hist, &raw, &post] {
+        let pairs = h
+            .iter_recorded()
+            .map(|v| (v.value_iterated_to(), v.count_at_value()));
+
+        let mut h2 = Histogram::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+        h2.record_pairs(pairs).unwrap();
+
+        assert_eq!(**h, h2, "histograms should be equal after recording pairs");
+        assert_eq!(h.len(), h2.len());
+    }
+}
+
+#[test]
+fn record_pairs_bulk_matches_record_n() {
+    let Loaded {
+        hist, raw, post, ..
+    } = load_histograms();
+
+    for h in &[&hist, &raw, &post] {
+        let pairs = h
+            .iter_recorded()
+            .map(|v| (v.value_iterated_to(), v.count_at_value()))
+            .collect::<Vec<_>>();
+
+        let mut per_pair = Histogram::<u64>::new(SIGFIG).unwrap();
+        let mut bulk = Histogram::<u64>::new(SIGFIG).unwrap();
+        for &(value, count) in pairs.iter().rev() {
+            per_pair.record_n(value, count).unwrap();
+        }
+        bulk.record_pairs_bulk(&pairs).unwrap();
+
+        assert_eq!(per_pair, bulk);
+        assert!(h.counts_eq(&bulk));
+        assert_eq!(per_pair.high(), bulk.high());
+        assert_eq!(per_pair.min(), bulk.min());
+        assert_eq!(per_pair.max(), bulk.max());
+        assert_eq!(per_pair.len(), bulk.len());
+    }
+}
+
+#[test]
+fn record_pairs_bulk_out_of_range_records_nothing() {
+    let mut h = Histogram::<u64>::new_with_max(1000, SIGFIG).unwrap();
+    h += 5;
+
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        h.record_pairs_bulk(&[(10, 1), (10_000, 3), (20, 4)])
+            .unwrap_err()
+    );
+    assert_eq!(0, h.count_at(10));
+    assert_eq!(1, h.len());
+
+    h.record_pairs_bulk(&[]).unwrap();
+    h.record_pairs_bulk(&[(0, 2), (7, 0)]).unwrap();
+    assert_eq!(3, h.len());
+    assert_eq!(0, h.min());
+    assert_eq!(7, h.max());
+}
+
+#[test]
+fn record_batch_matches_record_n() {
+    let Loaded { raw, post, .. } = load_histograms();
+
+    for h in &[&raw, &post] {
+        let mut per_pair = Histogram::<u64>::new(SIGFIG).unwrap();
+        let mut batched = Histogram::<u64>::new(SIGFIG).unwrap();
+        let mut batch = Histogram::batch();
+        for v in h.iter_recorded() {
+            per_pair
+                .record_n(v.value_iterated_to(), v.count_at_value())
+                .unwrap();
+            batch.add(v.value_iterated_to(), v.count_at_value());
+        }
+        assert_eq!(0, batched.len());
+        batch.commit(&mut batched).unwrap();
+
+        assert_eq!(per_pair, batched);
+        assert_eq!(per_pair.high(), batched.high());
+        assert_eq!(per_pair.min(), batched.min());
+        assert_eq!(per_pair.max(), batched.max());
+    }
+}
+
+#[test]
+fn record_batch_zero_values_match_record_n() {
+    let mut per_pair = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    per_pair.record_n(0, 1).unwrap();
+
+    let mut batched = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut batch = Histogram::batch();
+    batch.add(0, 1);
+    batch.commit(&mut batched).unwrap();
+
+    assert_eq!(per_pair, batched);
+    assert_eq!(0, batched.max());
+    assert_eq!(0, batched.min());
+}
+
+#[test]
+fn record_batch_error_records_nothing() {
+    let mut h = Histogram::<u64>::new_with_max(1000, SIGFIG).unwrap();
+    h += 5;
+
+    let mut batch = Histogram::batch();
+    batch.add(10, 1);
+    batch.add(10_000, 3);
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        batch.commit(&mut h).unwrap_err()
+    );
+    assert_eq!(0, h.count_at(10));
+    assert_eq!(1, h.len());
+    assert_eq!(5, h.max());
+}
+
+#[test]
+fn record_pairs_stops_at_first_error() {
+    let mut h = Histogram::<u64>::new_with_max(1000, SIGFIG).unwrap();
+
+    assert!(h
+        .record_pairs(vec![(10, 1), (100, 2), (10_000, 3), (20, 4)])
+        .is_err());
+    assert_eq!(1, h.count_at(10));
+    assert_eq!(2, h.count_at(100));
+    assert_eq!(0, h.count_at(20));
+    assert_eq!(3, h.len());
+}
+
+#[test]
+fn clone_correct_counted_synthetic_samples() {
+    let Loaded { raw, post, .. } = load_histograms();
+
+    let outlier = 100_000_000;
+    let (corrected, synthetic) = raw.clone_correct_counted(EINTERVAL);
+    assert_eq!(post, corrected);
+    assert_eq!(outlier / EINTERVAL - 1, synthetic);
+    assert_eq!(raw.len() + synthetic, corrected.len());
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(1_000, 500).unwrap();
+    h.record_n(outlier, 3).unwrap();
+    let (corrected, synthetic) = h.clone_correct_counted(EINTERVAL);
+    assert_eq!(3 * (outlier / EINTERVAL - 1), synthetic);
+    assert_eq!(h.len() + synthetic, corrected.len());
+
+    // nothing to correct
+    assert_eq!(0, raw.clone_correct_counted(0).1);
+    assert_eq!(0, raw.clone_correct_counted(outlier).1);
+}
+
+#[test]
+fn clone_correct_pathological_intervals() {
+    let mut h = Histogram::<u64>::new_with_max(u64::max_value(), SIGFIG).unwrap();
+    h += 1;
+    h += 1_000;
+    h += 1 << 62;
+
+    for &interval in &[0, u64::max_value(), h.max() / 2 + 1, h.max()] {
+        let corrected = h.clone_correct(interval);
+        assert_eq!(h, corrected, "interval {}", interval);
+    }
+
+    // backfills a single value just above half the max
+    let (corrected, synthetic) = h.clone_correct_counted(h.max() / 2 - 1);
+    assert_eq!(1, synthetic);
+    assert_eq!(h.len() + 1, corrected.len());
+    assert_eq!(h.max(), corrected.max());
+
+    // backfilled values below the lowest discernible value land in the zero bucket
+    let mut coarse = Histogram::<u8>::new_with_bounds(1000, 100_000, SIGFIG).unwrap();
+    coarse.record_n(5_000, 200).unwrap();
+    let (corrected, synthetic) = coarse.clone_correct_counted(1);
+    // the recorded value is corrected from the top of its bucket, one at a time down to 1
+    assert_eq!(200 * (coarse.highest_equivalent(5_000) - 1), synthetic);
+    assert_eq!(coarse.len() + synthetic, corrected.len());
+    assert_eq!(0, corrected.min());
+    assert_eq!(u8::max_value(), corrected.count_at(0));
+    // the backfill isn't clamping the user asked for
+    assert_eq!(0, corrected.clamped_low_count());
+    assert_eq!(0, corrected.clamped_high_count());
+
+    let mut h = Histogram::<u64>::new_with_bounds(1000, 100_000, SIGFIG).unwrap();
+    h.record_n(5_000, 2).unwrap();
+    let corrected = h.clone_correct(100);
+    assert!(corrected.len() > h.len());
+    assert_eq!(0, corrected.clamped_low_count());
+}
+
 #[test]
 fn total_count_exceeds_bucket_type() {
     let mut h: Histogram<u8> = Histogram::new(3).unwrap();
@@ -2,9 +2,15 @@
 
 use rand::{Rng, SeedableRng};
 
-use hdrhistogram::{Counter, Histogram, SubtractionError};
+use hdrhistogram::{
+    AdditionError, CorrectionStrategy, Counter, CreationError, Histogram, OutOfRangePolicy,
+    RecordError, SnapshotError, SubtractionError,
+};
 use std::borrow::Borrow;
+use std::cmp;
 use std::fmt;
+use std::num::NonZeroU64;
+use std::time;
 
 macro_rules! assert_near {
     ($a:expr, $b:expr, $tolerance:expr) => {{
@@ -46,6 +52,35 @@ fn construction_arg_ranges() {
     assert!(Histogram::<u64>::new_with_max(TRACKABLE_MAX, 6).is_err());
 }
 
+#[test]
+fn construction_capped_memory() {
+    // sigfig 5 over the full range needs millions of counts
+    assert_eq!(
+        CreationError::ExceedsMemoryBudget,
+        Histogram::<u64>::new_with_bounds_capped(1, u64::MAX, 5, 1 << 20).unwrap_err()
+    );
+
+    let h = Histogram::<u64>::new_with_bounds_capped(1, u64::MAX, 5, 1 << 30).unwrap();
+    let bytes = h.distinct_values() * std::mem::size_of::<u64>();
+    assert!(bytes > 1 << 20);
+
+    // the budget is inclusive
+    assert!(Histogram::<u64>::new_with_bounds_capped(1, u64::MAX, 5, bytes).is_ok());
+    assert_eq!(
+        CreationError::ExceedsMemoryBudget,
+        Histogram::<u64>::new_with_bounds_capped(1, u64::MAX, 5, bytes - 1).unwrap_err()
+    );
+
+    // counter size is taken into account
+    assert!(Histogram::<u8>::new_with_bounds_capped(1, u64::MAX, 5, bytes / 8).is_ok());
+
+    // invalid parameters are still reported as such
+    assert_eq!(
+        CreationError::SigFigExceedsMax,
+        Histogram::<u64>::new_with_bounds_capped(1, u64::MAX, 6, usize::MAX).unwrap_err()
+    );
+}
+
 #[test]
 fn empty_histogram() {
     let h = Histogram::<u64>::new(SIGFIG).unwrap();
@@ -77,12 +112,101 @@ fn record() {
     assert!(verify_max(h));
 }
 
+#[test]
+fn record_matches_record_n_at_boundaries() {
+    for &(low, high, auto) in &[
+        (1, TRACKABLE_MAX, false),
+        (1000, TRACKABLE_MAX, false),
+        (1, u64::max_value(), false),
+        (1, 2048, true),
+    ] {
+        let mut h = Histogram::<u64>::new_with_bounds(low, high, SIGFIG).unwrap();
+        h.auto(auto);
+        let mut expected = h.clone();
+
+        let top = h.highest_equivalent(high);
+        let values = [
+            0,
+            1,
+            low - 1,
+            low,
+            low + 1,
+            2047,
+            2048,
+            2049,
+            high - 1,
+            high,
+            top,
+            top.saturating_add(1),
+            high.saturating_mul(2),
+            u64::max_value(),
+        ];
+        for &v in &values {
+            assert_eq!(expected.record_n(v, 1), h.record(v));
+            assert_eq!(expected, h);
+            assert_eq!(expected.min_nz(), h.min_nz());
+            assert_eq!(expected.max(), h.max());
+            assert_eq!(expected.len(), h.len());
+        }
+    }
+}
+
+#[test]
+fn record_saturates_total_count() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(1, u64::max_value()).unwrap();
+    assert!(!h.total_count_saturated());
+
+    h.record(1000).unwrap();
+    assert_eq!(u64::max_value(), h.len());
+    assert!(h.total_count_saturated());
+    assert_eq!(1, h.count_at(1000));
+    assert_eq!(h.highest_equivalent(1000), h.max());
+}
+
+#[test]
+fn record_nonzero() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut expected = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.record_nonzero(NonZeroU64::new(42).unwrap()).unwrap();
+    expected.record(42).unwrap();
+    assert_eq!(expected, h);
+    assert_eq!(h.count_at(42), 1);
+
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        h.record_nonzero(NonZeroU64::new(3 * TRACKABLE_MAX).unwrap())
+            .unwrap_err()
+    );
+}
+
 #[test]
 fn record_past_trackable_max() {
     let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
     assert!(h.record(3 * TRACKABLE_MAX).is_err());
 }
 
+#[test]
+fn can_record() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let top = h.highest_equivalent(TRACKABLE_MAX);
+
+    for &v in &[0, 1, TEST_VALUE_LEVEL, TRACKABLE_MAX, top] {
+        assert!(h.can_record(v), "{}", v);
+    }
+    for &v in &[3 * TRACKABLE_MAX, u64::max_value()] {
+        assert!(!h.can_record(v), "{}", v);
+        assert!(h.record(v).is_err());
+    }
+
+    h.auto(true);
+    for &v in &[TEST_VALUE_LEVEL, 3 * TRACKABLE_MAX, u64::max_value()] {
+        assert!(h.can_record(v), "{}", v);
+        assert!(h.record(v).is_ok());
+    }
+}
+
 #[test]
 fn saturating_record() {
     let mut h = Histogram::<u64>::new_with_bounds(512, TRACKABLE_MAX, SIGFIG).unwrap();
@@ -99,6 +223,209 @@ fn saturating_record() {
     assert!(verify_max(h));
 }
 
+#[test]
+fn saturating_record_checked() {
+    let mut h = Histogram::<u64>::new_with_bounds(512, TRACKABLE_MAX, SIGFIG).unwrap();
+
+    assert!(!h.saturating_record_checked(1000 * 1000));
+    assert!(!h.saturating_record_checked(0));
+    assert!(h.saturating_record_checked(1)); // clamped below
+    assert!(h.saturating_record_checked(3 * TRACKABLE_MAX)); // clamped above
+
+    assert_eq!(h.count_at(1000 * 1000), 1);
+    assert_eq!(h.count_at(h.high()), 1);
+    assert_eq!(h.len(), 4);
+    assert!(verify_max(h));
+}
+
+#[test]
+fn saturating_record_clamp_counts() {
+    let mut h = Histogram::<u64>::new_with_bounds(512, TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.saturating_record(0);
+    h.saturating_record(1000);
+    h.saturating_record(1);
+    h.saturating_record_n(100, 3);
+    h.saturating_record(3 * TRACKABLE_MAX);
+    h.saturating_record_n(u64::MAX, 2);
+    assert!(h.saturating_record_checked(TRACKABLE_MAX + TRACKABLE_MAX / 2));
+    // only the saturating methods count clamps
+    assert!(h.record(5).is_ok());
+
+    assert_eq!(4, h.clamped_low_count());
+    assert_eq!(4, h.clamped_high_count());
+
+    h.reset();
+    assert_eq!(0, h.clamped_low_count());
+    assert_eq!(0, h.clamped_high_count());
+}
+
+#[test]
+fn out_of_range_policy_error() {
+    let h = Histogram::<u64>::new(SIGFIG).unwrap();
+    assert_eq!(OutOfRangePolicy::Resize, h.out_of_range_policy());
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(OutOfRangePolicy::Error, h.out_of_range_policy());
+    h.on_out_of_range(OutOfRangePolicy::Clamp);
+    h.on_out_of_range(OutOfRangePolicy::Error);
+    assert_eq!(OutOfRangePolicy::Error, h.out_of_range_policy());
+
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        h.record(3 * TRACKABLE_MAX).unwrap_err()
+    );
+    assert!(!h.can_record(3 * TRACKABLE_MAX));
+    assert!(h.is_empty());
+}
+
+#[test]
+fn out_of_range_policy_clamp() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.on_out_of_range(OutOfRangePolicy::Clamp);
+    assert_eq!(OutOfRangePolicy::Clamp, h.out_of_range_policy());
+    assert!(!h.is_auto_resize());
+    assert!(h.can_record(u64::MAX));
+
+    h += 3 * TRACKABLE_MAX;
+    h.record_n(u64::MAX, 2).unwrap();
+    h.record_correct(1000, 100).unwrap();
+    h += 1000;
+
+    assert_eq!(3, h.count_at(h.high()));
+    assert_eq!(3, h.clamped_high_count());
+    assert_eq!(2, h.count_at(1000));
+    assert_eq!(TRACKABLE_MAX, h.high());
+    assert!(verify_max(h));
+}
+
+#[test]
+fn out_of_range_policy_clamp_low() {
+    let mut h = Histogram::<u64>::new_with_bounds(1000, TRACKABLE_MAX, SIGFIG).unwrap();
+    h.on_out_of_range(OutOfRangePolicy::Clamp);
+
+    h.record(5).unwrap();
+    assert_eq!(1, h.clamped_low_count());
+    h.record_n(5, 1).unwrap();
+    assert_eq!(2, h.clamped_low_count());
+    h.record(0).unwrap();
+    h.record(1000).unwrap();
+    assert_eq!(2, h.clamped_low_count());
+    assert_eq!(4, h.len());
+}
+
+#[test]
+fn out_of_range_policy_clamp_bulk() {
+    let pairs = [(5, 2), (1000, 3), (1 << 40, 1), (u64::MAX, 4)];
+    let mut expected = Histogram::<u64>::new_with_bounds(1000, TRACKABLE_MAX, SIGFIG).unwrap();
+    expected.on_out_of_range(OutOfRangePolicy::Clamp);
+    let mut bulk = expected.clone();
+    let mut batched = expected.clone();
+
+    expected.record_pairs(pairs.iter().cloned()).unwrap();
+    bulk.record_pairs_bulk(&pairs).unwrap();
+    let mut batch = Histogram::batch();
+    for &(value, count) in &pairs {
+        batch.add(value, count);
+    }
+    batch.commit(&mut batched).unwrap();
+
+    for h in &[bulk, batched] {
+        assert_eq!(expected, *h);
+        assert_eq!(5, h.clamped_high_count());
+        assert_eq!(2, h.clamped_low_count());
+        assert_eq!(TRACKABLE_MAX, h.high());
+        assert_eq!(expected.max(), h.max());
+        assert_eq!(expected.min_nz(), h.min_nz());
+    }
+
+    // every value clamped high
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.on_out_of_range(OutOfRangePolicy::Clamp);
+    let mut expected = h.clone();
+    h.record_pairs_bulk(&[(1 << 40, 1)]).unwrap();
+    expected.record_n(1 << 40, 1).unwrap();
+    assert_eq!(1, h.count_at(h.high()));
+    assert_eq!(expected.min_nz(), h.min_nz());
+    assert_eq!(expected.max(), h.max());
+}
+
+#[test]
+fn out_of_range_policy_resize() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.on_out_of_range(OutOfRangePolicy::Resize);
+    assert!(h.is_auto_resize());
+
+    h += 3 * TRACKABLE_MAX;
+    assert!(h.high() >= 3 * TRACKABLE_MAX);
+    assert_eq!(1, h.count_at(3 * TRACKABLE_MAX));
+    assert_eq!(0, h.clamped_high_count());
+
+    // disabling auto-resize turns resizing into errors
+    h.auto(false);
+    assert_eq!(OutOfRangePolicy::Error, h.out_of_range_policy());
+    assert!(h.record(u64::MAX).is_err());
+}
+
+#[test]
+fn out_of_range_policy_auto() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.on_out_of_range(OutOfRangePolicy::Clamp);
+
+    // disabling auto-resize leaves clamping alone, whichever order they're called in
+    h.auto(false);
+    assert_eq!(OutOfRangePolicy::Clamp, h.out_of_range_policy());
+    h.record(u64::MAX).unwrap();
+    assert_eq!(1, h.clamped_high_count());
+
+    // enabling it replaces clamping
+    h.auto(true);
+    assert_eq!(OutOfRangePolicy::Resize, h.out_of_range_policy());
+    h.auto(false);
+    assert_eq!(OutOfRangePolicy::Error, h.out_of_range_policy());
+}
+
+#[test]
+fn record_n_coalesced() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.record_n_coalesced(TEST_VALUE_LEVEL, 3, 50);
+    assert_eq!(150, h.count_at(TEST_VALUE_LEVEL));
+    h.record_n_coalesced(1000, 1, 1);
+    assert_eq!(1, h.count_at(1000));
+    h.record_n_coalesced(2000, 7, 0);
+    assert_eq!(0, h.count_at(2000));
+    assert_eq!(151, h.len());
+    // no smaller values are backfilled
+    assert_eq!(0, h.count_between(0, TEST_VALUE_LEVEL - 1));
+
+    // values are clamped, like saturating_record
+    h.record_n_coalesced(3 * TRACKABLE_MAX, 2, 2);
+    assert_eq!(4, h.count_at(h.high()));
+    assert_eq!(4, h.clamped_high_count());
+}
+
+#[test]
+fn record_n_coalesced_u128_beyond_u64() {
+    let mut h = Histogram::<u128>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.record_n_coalesced(TEST_VALUE_LEVEL, u128::from(u64::MAX), 4);
+    assert_eq!(4 * u128::from(u64::MAX), h.count_at(TEST_VALUE_LEVEL));
+    h.record_n_coalesced(1000, u128::MAX / 2, 3);
+    assert_eq!(u128::MAX, h.count_at(1000));
+}
+
+#[test]
+fn record_n_coalesced_saturates() {
+    let mut h = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n_coalesced(TEST_VALUE_LEVEL, 100, 3);
+    assert_eq!(u8::max_value(), h.count_at(TEST_VALUE_LEVEL));
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n_coalesced(TEST_VALUE_LEVEL, u64::max_value() / 2, 3);
+    assert_eq!(u64::max_value(), h.count_at(TEST_VALUE_LEVEL));
+}
+
 #[test]
 fn record_in_interval() {
     let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
@@ -123,6 +450,215 @@ fn record_in_interval() {
     assert!(verify_max(h));
 }
 
+#[test]
+fn record_in_interval_value_equal_to_interval() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_correct(1000, 1000).unwrap();
+
+    // no backfill: the value is exactly what was expected
+    assert_eq!(h.count_at(1000), 1);
+    assert_eq!(h.len(), 1);
+}
+
+#[test]
+fn record_in_interval_value_slightly_above_interval() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_correct(1001, 1000).unwrap();
+
+    // still no backfill: a backfilled value would be 1, which is below the interval
+    assert_eq!(h.count_at(1001), 1);
+    assert_eq!(h.len(), 1);
+}
+
+#[test]
+fn record_in_interval_value_twice_interval() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_correct(2000, 1000).unwrap();
+
+    // one backfill at the interval itself
+    assert_eq!(h.count_at(2000), 1);
+    assert_eq!(h.count_at(1000), 1);
+    assert_eq!(h.len(), 2);
+}
+
+#[test]
+fn record_in_interval_value_slightly_above_twice_interval() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n_correct(2001, 3, 1000).unwrap();
+
+    // one backfill, with the same count as the recorded value
+    assert_eq!(h.count_at(2001), 3);
+    assert_eq!(h.count_at(1001), 3);
+    assert_eq!(h.count_at(1000), 0);
+    assert_eq!(h.len(), 6);
+}
+
+#[test]
+fn record_n_correct_with_strategies() {
+    let mut linear = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut corrected = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut none = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut plain = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    for &(value, count) in &[(500, 1), (2001, 3), (10_000, 2), (1_000_000, 1)] {
+        linear
+            .record_n_correct_with(value, count, 1000, CorrectionStrategy::Linear)
+            .unwrap();
+        corrected.record_n_correct(value, count, 1000).unwrap();
+        none.record_n_correct_with(value, count, 1000, CorrectionStrategy::None)
+            .unwrap();
+        plain.record_n(value, count).unwrap();
+    }
+
+    assert_eq!(corrected, linear);
+    assert_eq!(plain, none);
+    assert_eq!(7, none.len());
+    assert!(linear.len() > none.len());
+
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        none.record_n_correct_with(2 * TRACKABLE_MAX, 1, 1000, CorrectionStrategy::None)
+            .unwrap_err()
+    );
+}
+
+#[test]
+fn record_with_interval_matches_record_correct() {
+    let mut h1 = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    for _ in 0..1000 {
+        h1.record_correct(1000, 10_000).unwrap();
+        h2.record_with_interval(1000, 10_000).unwrap();
+    }
+    // a stall that hides the samples that should have been taken in the meantime
+    h1.record_correct(100_000_000, 10_000).unwrap();
+    h2.record_with_interval(100_000_000, 10_000).unwrap();
+
+    assert_eq!(h1, h2);
+    assert_eq!(h1.len(), h2.len());
+    assert!(h2.len() > 1001);
+}
+
+#[test]
+fn record_at_tracks_time_window() {
+    let epoch = time::UNIX_EPOCH;
+    let at = |secs| epoch + time::Duration::from_secs(secs);
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(None, h.start_time());
+    assert_eq!(None, h.end_time());
+
+    for &secs in &[1000, 400, 1500, 700, 1200] {
+        h.record_at(TEST_VALUE_LEVEL, at(secs)).unwrap();
+    }
+    assert_eq!(5, h.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(Some(at(400)), h.start_time());
+    assert_eq!(Some(at(1500)), h.end_time());
+
+    // failed records don't change the window
+    assert!(h.record_at(2 * TRACKABLE_MAX, at(2000)).is_err());
+    assert_eq!(Some(at(1500)), h.end_time());
+
+    // carried over by new_from, and combined by add
+    let mut other = Histogram::<u64>::new_from(&h);
+    assert_eq!(Some(at(400)), other.start_time());
+    assert_eq!(Some(at(1500)), other.end_time());
+    other.reset();
+    assert_eq!(None, other.start_time());
+    other.record_at(TEST_VALUE_LEVEL, at(100)).unwrap();
+    h.add(&other).unwrap();
+    assert_eq!(Some(at(100)), h.start_time());
+    assert_eq!(Some(at(1500)), h.end_time());
+
+    let mut empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    empty.add(&h).unwrap();
+    assert_eq!(Some(at(100)), empty.start_time());
+    assert_eq!(Some(at(1500)), empty.end_time());
+
+    h.reset();
+    assert_eq!(None, h.start_time());
+    assert_eq!(None, h.end_time());
+}
+
+#[test]
+fn record_at_tracks_min_and_max_times() {
+    let epoch = time::UNIX_EPOCH;
+    let at = |secs| epoch + time::Duration::from_secs(secs);
+
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(None, h.min_value_time());
+    assert_eq!(None, h.max_value_time());
+
+    h.record_at(1000, at(10)).unwrap();
+    assert_eq!(Some(at(10)), h.min_value_time());
+    assert_eq!(Some(at(10)), h.max_value_time());
+
+    // a later sample sets a new max
+    h.record_at(5000, at(20)).unwrap();
+    assert_eq!(Some(at(10)), h.min_value_time());
+    assert_eq!(Some(at(20)), h.max_value_time());
+
+    // neither a new min nor max, even if in the same bucket as the max
+    h.record_at(3000, at(30)).unwrap();
+    h.record_at(5000, at(40)).unwrap();
+    assert_eq!(Some(at(10)), h.min_value_time());
+    assert_eq!(Some(at(20)), h.max_value_time());
+
+    // samples may be recorded out of order
+    h.record_at(10, at(5)).unwrap();
+    assert_eq!(Some(at(5)), h.min_value_time());
+    assert_eq!(Some(at(20)), h.max_value_time());
+
+    // failed records don't change anything
+    assert!(h.record_at(2 * TRACKABLE_MAX, at(50)).is_err());
+    assert_eq!(Some(at(20)), h.max_value_time());
+
+    h.reset();
+    assert_eq!(None, h.min_value_time());
+    assert_eq!(None, h.max_value_time());
+}
+
+#[test]
+fn record_i64_clamps_negative_to_low() {
+    let mut h = Histogram::<u64>::new_with_bounds(10, TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.record_i64(-1).unwrap();
+    h.record_i64(i64::min_value()).unwrap();
+    h.record_i64(12_345).unwrap();
+
+    assert_eq!(3, h.len());
+    assert_eq!(2, h.count_at(10));
+    assert_eq!(1, h.count_at(12_345));
+    assert_eq!(h.lowest_equivalent(10), h.min());
+    assert_eq!(h.highest_equivalent(12_345), h.max());
+    assert!(h.record_i64(i64::max_value()).is_err());
+}
+
+#[test]
+fn record_then_quantile_increasing_sequence() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    let mut last = 0.0;
+    for v in 1..=10_000 {
+        let q = h.record_then_quantile(v * 100).unwrap();
+        assert!(q >= last, "value {}: {} < {}", v, q, last);
+        // the new sample is the largest so far
+        assert_eq!(1.0, q);
+        last = q;
+    }
+    assert_eq!(10_000, h.len());
+
+    // a small value lands near the bottom
+    assert_near!(
+        2.0 / 10_001.0,
+        h.record_then_quantile(100).unwrap(),
+        0.000_001
+    );
+    assert!(h.record_then_quantile(2 * TRACKABLE_MAX).is_err());
+    assert_eq!(10_001, h.len());
+}
+
 #[test]
 fn reset() {
     let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
@@ -134,6 +670,74 @@ fn reset() {
     assert!(verify_max(h));
 }
 
+#[test]
+fn scale_counts_by_one() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 3).unwrap();
+    h.record_n(1000 * TEST_VALUE_LEVEL, 5).unwrap();
+    let orig = h.clone();
+
+    h.scale_counts(1);
+    are_equal(h, orig);
+}
+
+#[test]
+fn scale_counts_by_two() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 3).unwrap();
+    h.record_n(1000 * TEST_VALUE_LEVEL, 5).unwrap();
+
+    h.scale_counts(2);
+    assert_eq!(6, h.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(10, h.count_at(1000 * TEST_VALUE_LEVEL));
+    assert_eq!(16, h.len());
+    assert!(verify_max(h));
+}
+
+#[test]
+fn scale_counts_saturates() {
+    let mut h = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 100).unwrap();
+    h.record_n(1000 * TEST_VALUE_LEVEL, 10).unwrap();
+
+    h.scale_counts(3);
+    assert_eq!(u8::MAX, h.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(30, h.count_at(1000 * TEST_VALUE_LEVEL));
+    assert_eq!(285, h.len());
+}
+
+#[test]
+fn scale_counts_u128_beyond_u64() {
+    let big = 4 * u128::from(u64::MAX);
+    let mut h = Histogram::<u128>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, big).unwrap();
+    h.record_n(1000 * TEST_VALUE_LEVEL, u128::MAX / 2).unwrap();
+
+    h.scale_counts(1);
+    assert_eq!(big, h.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(u128::MAX / 2, h.count_at(1000 * TEST_VALUE_LEVEL));
+
+    h.scale_counts(3);
+    assert_eq!(3 * big, h.count_at(TEST_VALUE_LEVEL));
+    assert_eq!(u128::MAX, h.count_at(1000 * TEST_VALUE_LEVEL));
+
+    // a factor that doesn't fit in the counter type saturates
+    let mut small = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    small.record_n(TEST_VALUE_LEVEL, 1).unwrap();
+    small.scale_counts(1000);
+    assert_eq!(u8::MAX, small.count_at(TEST_VALUE_LEVEL));
+}
+
+#[test]
+fn scale_counts_by_zero() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 3).unwrap();
+
+    h.scale_counts(0);
+    assert_eq!(0, h.count_at(TEST_VALUE_LEVEL));
+    assert!(h.is_empty());
+}
+
 #[test]
 fn add() {
     let mut h1 = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
@@ -169,6 +773,301 @@ fn add() {
     assert!(verify_max(big));
 }
 
+/// Add `source` to a copy of `dest` by recording each of its values, like the general slow path.
+fn add_by_recording(dest: &Histogram<u64>, source: &Histogram<u64>) -> Histogram<u64> {
+    let mut expected = dest.clone();
+    for v in source.iter_recorded() {
+        expected
+            .record_n(
+                source.lowest_equivalent(v.value_iterated_to()),
+                v.count_at_value(),
+            )
+            .unwrap();
+    }
+    expected
+}
+
+#[test]
+fn add_unit_magnitude_shift_matches_recording() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+
+    for &shift in &[0_u32, 1, 3, 10, 20] {
+        let low = 1_u64 << shift;
+        let mut source = Histogram::<u64>::new_with_bounds(low, u64::MAX, SIGFIG).unwrap();
+        // lower half of the first bucket, the rest of the first bucket, and beyond
+        for &v in &[low, 5 * low, 1000 * low, 2047 * low, 4000 * low] {
+            source.record(v).unwrap();
+        }
+        for _ in 0..1000 {
+            source.record(rng.gen_range(low..=u64::MAX / 2)).unwrap();
+        }
+
+        // both when dest has fewer buckets than source and when it doesn't
+        for &high in &[2, u64::MAX] {
+            let mut dest = Histogram::<u64>::new_with_bounds(1, high, SIGFIG).unwrap();
+            dest.auto(true);
+            dest.record_n(3, 2).unwrap();
+            dest.record(1 << 40).unwrap();
+
+            let expected = add_by_recording(&dest, &source);
+            dest.add(&source).unwrap();
+
+            assert_eq!(expected, dest, "shift {} high {}", shift, high);
+            assert_eq!(expected.len(), dest.len());
+            assert_eq!(expected.min_nz(), dest.min_nz());
+            assert_eq!(expected.max(), dest.max());
+        }
+    }
+}
+
+#[test]
+fn add_unit_magnitude_shift_coarser_dest_matches_recording() {
+    let mut source = Histogram::<u64>::new_with_bounds(1, u64::MAX, SIGFIG).unwrap();
+    for v in 1..10_000 {
+        source.record(v * 7).unwrap();
+    }
+    let dest = Histogram::<u64>::new_with_bounds(1024, u64::MAX, SIGFIG).unwrap();
+
+    let expected = add_by_recording(&dest, &source);
+    let mut actual = dest.clone();
+    actual.add(&source).unwrap();
+
+    assert_eq!(expected, actual);
+    assert_eq!(expected.len(), actual.len());
+}
+
+#[test]
+fn count_diff() {
+    let mut a = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    a.record_n(10, 5).unwrap();
+    a.record_n(100, 5).unwrap();
+    b.record_n(10, 5).unwrap();
+    b.record_n(100, 2).unwrap();
+    // b has extra counts in the tail
+    b.record_n(1_000_000, 3).unwrap();
+    b.record(2_000_000).unwrap();
+
+    assert_eq!(
+        vec![
+            (10, 0),
+            (100, 3),
+            (b.lowest_equivalent(1_000_000), -3),
+            (b.lowest_equivalent(2_000_000), -1),
+        ],
+        a.count_diff(&b).unwrap()
+    );
+    assert_eq!(
+        vec![
+            (10, 0),
+            (100, -3),
+            (b.lowest_equivalent(1_000_000), 3),
+            (b.lowest_equivalent(2_000_000), 1),
+        ],
+        b.count_diff(&a).unwrap()
+    );
+    assert!(a.count_diff(&a).unwrap().iter().all(|&(_, d)| d == 0));
+}
+
+#[test]
+fn count_diff_different_precision() {
+    let mut a = Histogram::<u64>::new_with_max(TRACKABLE_MAX, 2).unwrap();
+    let mut b = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    a.record_n(1000, 2).unwrap();
+    b.record(1000).unwrap();
+    b.record(1001).unwrap();
+    b.record(1_000_000).unwrap();
+
+    // b's two values fall in a's bucket for 1000
+    assert_eq!(
+        vec![
+            (a.lowest_equivalent(1000), 0),
+            (a.lowest_equivalent(1_000_000), -1),
+        ],
+        a.count_diff(&b).unwrap()
+    );
+}
+
+#[test]
+fn count_diff_other_exceeds_range() {
+    let a = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u64>::new_with_max(2 * TRACKABLE_MAX, SIGFIG).unwrap();
+    b += 2 * TRACKABLE_MAX;
+
+    assert_eq!(
+        AdditionError::OtherAddendValueExceedsRange,
+        a.count_diff(&b).unwrap_err()
+    );
+}
+
+#[test]
+fn add_checked_bucket_overflow() {
+    let mut a = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    a.record_n(1000, 200).unwrap();
+    b.record_n(1000, 100).unwrap();
+    b.record(5).unwrap();
+
+    let mut saturated = a.clone();
+    saturated.add(&b).unwrap();
+    assert_eq!(255, saturated.count_at(1000));
+
+    assert_eq!(AdditionError::CountOverflow, a.add_checked(&b).unwrap_err());
+    // nothing was added
+    assert_eq!(200, a.count_at(1000));
+    assert_eq!(0, a.count_at(5));
+    assert_eq!(200, a.len());
+}
+
+#[test]
+fn add_checked_overflow_across_merged_buckets() {
+    // b's buckets are finer than a's, so two of b's buckets land in one of a's
+    let mut a = Histogram::<u8>::new_with_max(TRACKABLE_MAX, 1).unwrap();
+    let mut b = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    b.record_n(1000, 200).unwrap();
+    b.record_n(1001, 100).unwrap();
+    assert_eq!(a.lowest_equivalent(1000), a.lowest_equivalent(1001));
+    assert_ne!(b.lowest_equivalent(1000), b.lowest_equivalent(1001));
+
+    assert_eq!(AdditionError::CountOverflow, a.add_checked(&b).unwrap_err());
+    assert!(a.is_empty());
+}
+
+#[test]
+fn add_checked_total_count_overflow() {
+    let mut a = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    a.record_n(1000, u64::max_value() - 10).unwrap();
+    b.record_n(5, 100).unwrap();
+
+    let mut saturated = a.clone();
+    saturated.add(&b).unwrap();
+    assert_eq!(u64::max_value(), saturated.len());
+    assert!(saturated.total_count_saturated());
+
+    assert_eq!(
+        AdditionError::TotalCountOverflow,
+        a.add_checked(&b).unwrap_err()
+    );
+    // nothing was added
+    assert_eq!(0, a.count_at(5));
+    assert_eq!(u64::max_value() - 10, a.len());
+
+    // an already saturated total can't be added to exactly either
+    assert_eq!(
+        AdditionError::TotalCountOverflow,
+        saturated.add_checked(&b).unwrap_err()
+    );
+    assert_eq!(
+        AdditionError::TotalCountOverflow,
+        b.add_checked(&saturated).unwrap_err()
+    );
+    assert_eq!(100, b.len());
+
+    // up to the max is fine
+    let mut c = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    c.record_n(5, 10).unwrap();
+    a.add_checked(&c).unwrap();
+    assert_eq!(u64::max_value(), a.len());
+    assert!(!a.total_count_saturated());
+}
+
+#[test]
+fn add_checked_no_overflow() {
+    let mut a = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    a.record_n(1000, 200).unwrap();
+    b.record_n(1000, 55).unwrap();
+    b.record(5).unwrap();
+
+    a.add_checked(&b).unwrap();
+    assert_eq!(255, a.count_at(1000));
+    assert_eq!(1, a.count_at(5));
+    assert_eq!(256, a.len());
+}
+
+#[test]
+fn counts_eq_ignores_saturated_total() {
+    let mut a = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    a.record_n(1000, 200).unwrap();
+    a.record_n(1000, 200).unwrap();
+    b.record_n(1000, 255).unwrap();
+    assert_eq!(a.count_at(1000), b.count_at(1000));
+    assert_ne!(a.len(), b.len());
+
+    assert!(a != b);
+    assert!(a.counts_eq(&b));
+    assert!(b.counts_eq(&a));
+
+    b.record(5).unwrap();
+    assert!(!a.counts_eq(&b));
+}
+
+#[test]
+fn counts_eq_different_lengths_and_config() {
+    let mut a = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut b = Histogram::<u64>::new_with_max(1000 * TRACKABLE_MAX, SIGFIG).unwrap();
+    assert!(a.distinct_values() < b.distinct_values());
+    a += 1000;
+    b += 1000;
+    assert!(a.counts_eq(&b));
+    assert!(b.counts_eq(&a));
+
+    b += 100 * TRACKABLE_MAX;
+    assert!(!a.counts_eq(&b));
+    assert!(!b.counts_eq(&a));
+
+    let mut c = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG - 1).unwrap();
+    c += 1000;
+    assert!(!a.counts_eq(&c));
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn sample_reproduces_quantiles() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    for _ in 0..100_000 {
+        // skewed towards small values, with a long tail
+        h += rng.gen_range(1..1000_u64) * rng.gen_range(1..1000_u64);
+    }
+
+    let mut sampled = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    for _ in 0..100_000 {
+        sampled += h.sample(&mut rng);
+    }
+    let mut sampled_n = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    for v in h.sample_n(&mut rng, 100_000) {
+        sampled_n += v;
+    }
+
+    for s in &[&sampled, &sampled_n] {
+        assert_eq!(100_000, s.len());
+        assert!(s.min() >= h.lowest_equivalent(h.min()));
+        assert!(s.max() <= h.max());
+        for &q in &[0.1, 0.5, 0.9, 0.99] {
+            assert_near!(h.value_at_quantile(q), s.value_at_quantile(q), 0.02);
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn sample_single_bucket_and_empty() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(0, h.sample(&mut rng));
+    assert_eq!(vec![0, 0], h.sample_n(&mut rng, 2));
+
+    h.record_n(10_000, 5).unwrap();
+    let expected = h.median_equivalent(10_000);
+    assert_eq!(expected, h.sample(&mut rng));
+    assert!(h.sample_n(&mut rng, 100).iter().all(|&v| v == expected));
+}
+
 #[test]
 fn equivalent_range() {
     let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
@@ -189,6 +1088,139 @@ fn scaled_equivalent_range() {
     assert_eq!(h.equivalent_range(10_000 * 1024), 8 * 1024);
 }
 
+#[test]
+fn relative_error_at() {
+    let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_eq!(1.0 / 1000.0, h.relative_error_at(1000));
+    assert_eq!(8.0 / 10_000.0, h.relative_error_at(10_000));
+    assert_eq!(512.0 / 1_000_000.0, h.relative_error_at(1_000_000));
+    assert_eq!(f64::INFINITY, h.relative_error_at(0));
+}
+
+#[test]
+fn relative_error_at_within_sigfig() {
+    for sigfig in 0..=5 {
+        for &low in &[1, 1000, 1 << 20] {
+            let h = Histogram::<u64>::new_with_bounds(low, u64::MAX, sigfig).unwrap();
+            let max_error = 10_f64.powi(-i32::from(sigfig));
+            let mut value = low * 10_u64.pow(u32::from(sigfig));
+            while value < u64::MAX / 3 {
+                for &v in &[value, value + 1, value * 2 - 1] {
+                    assert!(
+                        h.relative_error_at(v) <= max_error,
+                        "low {} sigfig {} value {}",
+                        low,
+                        sigfig,
+                        v
+                    );
+                }
+                value = cmp::max(value + 1, value / 2 * 3);
+            }
+        }
+    }
+}
+
+#[test]
+fn new_from_different_counter_type() {
+    let mut h = Histogram::<u64>::new_with_bounds(10, TRACKABLE_MAX, SIGFIG).unwrap();
+    h.auto(true);
+    h.record_n(TEST_VALUE_LEVEL, u64::max_value()).unwrap();
+    h += 2 * TRACKABLE_MAX;
+
+    let mut narrow = Histogram::<u32>::new_from(&h);
+    assert!(narrow.is_empty());
+    assert_eq!(h.low(), narrow.low());
+    assert_eq!(h.high(), narrow.high());
+    assert_eq!(h.sigfig(), narrow.sigfig());
+    assert_eq!(h.distinct_values(), narrow.distinct_values());
+    assert!(narrow.is_auto_resize());
+
+    // same bucket layout, so values land in the same buckets
+    narrow += TEST_VALUE_LEVEL;
+    assert_eq!(1, narrow.count_at(TEST_VALUE_LEVEL));
+    narrow += 2 * TRACKABLE_MAX;
+    assert_eq!(h.max(), narrow.max());
+
+    let wide = Histogram::<u128>::new_from(&narrow);
+    assert!(wide.is_empty());
+    assert_eq!(narrow.distinct_values(), wide.distinct_values());
+}
+
+#[test]
+fn matches_config() {
+    let mut h = Histogram::<u64>::new_with_bounds(10, TRACKABLE_MAX, SIGFIG).unwrap();
+    assert!(h.matches_config(10, TRACKABLE_MAX, SIGFIG));
+    assert!(!h.matches_config(1, TRACKABLE_MAX, SIGFIG));
+    assert!(!h.matches_config(11, TRACKABLE_MAX, SIGFIG));
+    assert!(!h.matches_config(10, TRACKABLE_MAX - 1, SIGFIG));
+    assert!(!h.matches_config(10, TRACKABLE_MAX + 1, SIGFIG));
+    assert!(!h.matches_config(10, TRACKABLE_MAX, SIGFIG - 1));
+    assert!(!h.matches_config(10, TRACKABLE_MAX, SIGFIG + 1));
+
+    // recording doesn't change the config
+    h += 1000;
+    assert!(h.matches_config(10, TRACKABLE_MAX, SIGFIG));
+
+    let c = Histogram::<u32>::new_from(&h);
+    assert!(c.matches_config(10, TRACKABLE_MAX, SIGFIG));
+    assert!(c.is_empty());
+}
+
+#[test]
+fn new_with_error_sigfig() {
+    let h = Histogram::<u64>::new_with_error(1, TRACKABLE_MAX, 0.001).unwrap();
+    assert_eq!(3, h.sigfig());
+    assert_eq!(1, h.low());
+    assert_eq!(TRACKABLE_MAX, h.high());
+
+    let sigfig = |error| {
+        Histogram::<u64>::new_with_error(1, TRACKABLE_MAX, error)
+            .unwrap()
+            .sigfig()
+    };
+    assert_eq!(0, sigfig(1.0));
+    assert_eq!(0, sigfig(2.0));
+    assert_eq!(1, sigfig(0.5));
+    assert_eq!(1, sigfig(0.1));
+    assert_eq!(2, sigfig(0.05));
+    assert_eq!(2, sigfig(0.01));
+    assert_eq!(3, sigfig(0.002));
+    assert_eq!(5, sigfig(0.00001));
+
+    for &error in &[0.000_009, 0.0, -0.1, f64::NAN] {
+        assert_eq!(
+            CreationError::SigFigExceedsMax,
+            Histogram::<u64>::new_with_error(1, TRACKABLE_MAX, error).unwrap_err()
+        );
+    }
+    assert_eq!(
+        CreationError::HighLessThanTwiceLow { min_high: 20 },
+        Histogram::<u64>::new_with_error(10, 15, 0.01).unwrap_err()
+    );
+}
+
+#[test]
+fn new_with_error_relative_error_within_bound() {
+    for &error in &[0.1, 0.03, 0.01, 0.001, 0.0005] {
+        for &low in &[1, 1000] {
+            let h = Histogram::<u64>::new_with_error(low, TRACKABLE_MAX, error).unwrap();
+            let mut value = low * 10_u64.pow(u32::from(h.sigfig()));
+            while value < TRACKABLE_MAX {
+                for &v in &[value, value + 1, value * 2 - 1] {
+                    assert!(
+                        h.relative_error_at(v) <= error,
+                        "low {} error {} value {}",
+                        low,
+                        error,
+                        v
+                    );
+                }
+                value = cmp::max(value + 1, value / 2 * 3);
+            }
+        }
+    }
+}
+
 #[test]
 fn lowest_equivalent() {
     let h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
@@ -264,6 +1296,7 @@ where
     let expected = expected.borrow();
 
     assert_eq!(actual, expected);
+    assert_eq!(actual.fingerprint(), expected.fingerprint());
     assert_eq!(
         actual.count_at(TEST_VALUE_LEVEL),
         expected.count_at(TEST_VALUE_LEVEL)
@@ -289,6 +1322,115 @@ fn clone() {
     are_equal(h.clone(), h);
 }
 
+#[test]
+fn try_clone() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h += TEST_VALUE_LEVEL;
+    h += 10 * TEST_VALUE_LEVEL;
+
+    let max = h.high();
+    h.record_correct(max - 1, 31_000).unwrap();
+
+    are_equal(h.try_clone().unwrap(), h);
+}
+
+#[test]
+fn new_from_snapshot_counts() {
+    let mut h = Histogram::<u32>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 10).unwrap();
+    h.record_n(1000 * TEST_VALUE_LEVEL, 20).unwrap();
+
+    let counts = h
+        .iter_all()
+        .map(|v| u64::from(v.count_at_value()))
+        .collect::<Vec<u64>>();
+
+    let h2 = Histogram::<u32>::new_from_snapshot_counts(1, TRACKABLE_MAX, SIGFIG, &counts).unwrap();
+    assert_eq!(h2, h);
+    assert_eq!(30, h2.len());
+}
+
+#[test]
+fn to_snapshot_counts_round_trip() {
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert!(empty.to_snapshot_counts().is_empty());
+
+    let mut single = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    single.record(TEST_VALUE_LEVEL).unwrap();
+
+    let mut multiple = Histogram::<u64>::new_with_bounds(1000, TRACKABLE_MAX, 2).unwrap();
+    for v in &[
+        1000,
+        4000,
+        TEST_VALUE_LEVEL,
+        1000 * TEST_VALUE_LEVEL,
+        TRACKABLE_MAX,
+    ] {
+        multiple.record_n(*v, 7).unwrap();
+    }
+
+    let mut max_counts = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    max_counts.record_n(1, 255).unwrap();
+    max_counts.record_n(TRACKABLE_MAX, 255).unwrap();
+
+    for h in &[&empty, &single, &multiple] {
+        let h2 = Histogram::<u64>::new_from_snapshot_counts(
+            h.low(),
+            h.high(),
+            h.sigfig(),
+            &h.to_snapshot_counts(),
+        )
+        .unwrap();
+        assert_eq!(**h, h2);
+        assert_eq!(h.len(), h2.len());
+        assert_eq!(h.max(), h2.max());
+        assert_eq!(h.min_nz(), h2.min_nz());
+    }
+
+    let counts = max_counts.to_snapshot_counts();
+    // trailing zeros are trimmed
+    assert_eq!(Some(&255), counts.last());
+    assert!(counts.len() <= max_counts.distinct_values());
+    let h2 = Histogram::<u8>::new_from_snapshot_counts(
+        max_counts.low(),
+        max_counts.high(),
+        max_counts.sigfig(),
+        &counts,
+    )
+    .unwrap();
+    assert_eq!(max_counts, h2);
+}
+
+#[test]
+fn new_from_snapshot_counts_exceeding_counter_type() {
+    let counts = [0, 1, u64::from(u32::MAX) + 1];
+
+    assert_eq!(
+        SnapshotError::UnsuitableCounterType,
+        Histogram::<u32>::new_from_snapshot_counts(1, TRACKABLE_MAX, SIGFIG, &counts).unwrap_err()
+    );
+
+    // a wider counter type is fine
+    let h = Histogram::<u64>::new_from_snapshot_counts(1, TRACKABLE_MAX, SIGFIG, &counts).unwrap();
+    assert_eq!(u64::from(u32::MAX) + 2, h.len());
+    assert_eq!(1, h.min());
+    assert_eq!(2, h.max());
+}
+
+#[test]
+fn new_from_snapshot_counts_invalid() {
+    assert_eq!(
+        SnapshotError::CreationError(CreationError::LowIsZero),
+        Histogram::<u64>::new_from_snapshot_counts(0, TRACKABLE_MAX, SIGFIG, &[]).unwrap_err()
+    );
+
+    let counts = vec![0; 100_000];
+    assert_eq!(
+        SnapshotError::CountsArrayTooLong,
+        Histogram::<u64>::new_from_snapshot_counts(1, 1000, SIGFIG, &counts).unwrap_err()
+    );
+}
+
 #[test]
 fn scaled_clone() {
     let mut h = Histogram::<u64>::new_with_bounds(1000, TRACKABLE_MAX, SIGFIG).unwrap();
@@ -425,6 +1567,32 @@ fn value_count_overflow_from_record_autoresize_doesnt_panic_saturates() {
     assert_eq!(u64::max_value(), h.len());
 }
 
+#[test]
+fn u128_counts_beyond_u64() {
+    let mut h = Histogram::<u128>::new_with_bounds(1, u64::MAX, 3).unwrap();
+    let big = u128::from(u64::MAX) + 10;
+
+    h.record_n(1000, big).unwrap();
+    assert_eq!(big, h.count_at(1000));
+    assert_eq!(u64::MAX, h.len());
+    assert!(h.total_count_saturated());
+
+    h.record_n(1000, big).unwrap();
+    h.record(10).unwrap();
+    assert_eq!(2 * big, h.count_at(1000));
+    assert_eq!(1, h.count_at(10));
+    assert_eq!(u64::MAX, h.len());
+    assert_eq!(10, h.min());
+    assert_eq!(1000, h.max());
+
+    // restat agrees
+    let mut h2 = Histogram::<u128>::new_from(&h);
+    h2.add(&h).unwrap();
+    h2.subtract(&h).unwrap();
+    assert_eq!(0, h2.len());
+    assert!(!h2.total_count_saturated());
+}
+
 #[test]
 fn value_count_overflow_from_add_same_dimensions_saturates() {
     let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
@@ -480,6 +1648,223 @@ fn total_count_overflow_from_record_saturates() {
     assert_eq!(u64::max_value(), h.len());
 }
 
+#[test]
+fn total_count_saturated_flag_from_record() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+
+    h.record_n(1, u64::MAX - 1).unwrap();
+    assert!(!h.total_count_saturated());
+    h.record(10).unwrap();
+    // exactly u64::MAX is still accurate
+    assert!(!h.total_count_saturated());
+    h.record(10).unwrap();
+    assert!(h.total_count_saturated());
+    assert_eq!(u64::MAX, h.len());
+
+    h.reset();
+    assert!(!h.total_count_saturated());
+    assert_eq!(0, h.len());
+}
+
+#[test]
+fn record_n_total_checked_errors_instead_of_saturating() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+
+    h.record_n_total_checked(1, u64::MAX - 10).unwrap();
+    h.record_n_total_checked(10, 9).unwrap();
+    assert_eq!(u64::MAX - 1, h.len());
+
+    // would overflow the total by one
+    assert_eq!(
+        RecordError::TotalCountOverflow,
+        h.record_n_total_checked(10, 2).unwrap_err()
+    );
+    assert_eq!(9, h.count_at(10));
+    assert_eq!(u64::MAX - 1, h.len());
+    assert!(!h.total_count_saturated());
+
+    // exactly u64::MAX is fine
+    h.record_n_total_checked(10, 1).unwrap();
+    assert_eq!(u64::MAX, h.len());
+    assert_eq!(
+        RecordError::TotalCountOverflow,
+        h.record_n_total_checked(10, 1).unwrap_err()
+    );
+    h.record_n_total_checked(10, 0).unwrap();
+
+    // record_n still saturates
+    h.record_n(10, 1).unwrap();
+    assert!(h.total_count_saturated());
+}
+
+#[test]
+fn record_n_total_checked_wide_counter() {
+    let mut h = Histogram::<u128>::new_with_bounds(1, u64::MAX, 3).unwrap();
+
+    assert_eq!(
+        RecordError::TotalCountOverflow,
+        h.record_n_total_checked(1, u128::from(u64::MAX) + 1)
+            .unwrap_err()
+    );
+    assert!(h.is_empty());
+}
+
+#[test]
+fn record_weighted_rounds() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    h.record_weighted(100, 2.4).unwrap();
+    h.record_weighted(200, 2.5).unwrap();
+    h.record_weighted(300, 0.6).unwrap();
+    h.record_weighted(400, 0.4).unwrap();
+    h.record_weighted(500, 0.0).unwrap();
+    h.record_weighted(600, 7.0).unwrap();
+
+    assert_eq!(2, h.count_at(100));
+    assert_eq!(3, h.count_at(200));
+    assert_eq!(1, h.count_at(300));
+    assert_eq!(0, h.count_at(400));
+    assert_eq!(0, h.count_at(500));
+    assert_eq!(7, h.count_at(600));
+    assert_eq!(13, h.len());
+    assert_eq!(h.lowest_equivalent(100), h.min());
+    assert_eq!(h.highest_equivalent(600), h.max());
+
+    let mut narrow = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    narrow.record_weighted(100, 1e10).unwrap();
+    assert_eq!(u8::max_value(), narrow.count_at(100));
+}
+
+#[test]
+fn record_weighted_invalid_weight() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+
+    for &weight in &[f64::NAN, -1.0, -0.1, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(
+            RecordError::InvalidWeight,
+            h.record_weighted(100, weight).unwrap_err()
+        );
+    }
+    assert!(h.is_empty());
+
+    assert_eq!(
+        RecordError::ValueOutOfRangeResizeDisabled,
+        h.record_weighted(3 * TRACKABLE_MAX, 1.0).unwrap_err()
+    );
+}
+
+#[test]
+fn total_count_saturated_flag_from_add() {
+    let mut h1 = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+    let mut h2 = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+    let mut h3 = Histogram::<u64>::new_with_bounds(1, u64::MAX, 2).unwrap();
+
+    h1.record_n(1, u64::MAX - 1).unwrap();
+    h2.record_n(10, u64::MAX - 1).unwrap();
+    h3.record_n(10, u64::MAX - 1).unwrap();
+
+    let mut same = h1.clone();
+    same.add(&h2).unwrap();
+    assert!(same.total_count_saturated());
+
+    let mut different = h1.clone();
+    different.add(&h3).unwrap();
+    assert!(different.total_count_saturated());
+
+    // restat after subtracting finds an accurate total again
+    same.subtract(&h2).unwrap();
+    assert!(!same.total_count_saturated());
+    assert_eq!(u64::MAX - 1, same.len());
+}
+
+#[test]
+fn has_saturated_bucket_u8() {
+    let mut h = Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(1000, 254).unwrap();
+    assert!(!h.has_saturated_bucket());
+
+    h.record(1000).unwrap();
+    assert_eq!(255, h.count_at(1000));
+    assert!(h.has_saturated_bucket());
+
+    h.reset();
+    h.record_n(5, 255).unwrap();
+    assert!(h.has_saturated_bucket());
+
+    h.reset();
+    assert!(!h.has_saturated_bucket());
+}
+
+#[test]
+fn has_saturated_bucket_u64_normal_load() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    for v in 1..100_000 {
+        h.record(v).unwrap();
+        h.record_n(1000, 1000).unwrap();
+    }
+    assert!(!h.has_saturated_bucket());
+}
+
+#[test]
+fn fingerprint_equal_histograms() {
+    let mut h = Histogram::<u64>::new(SIGFIG).unwrap();
+    let mut wide = Histogram::<u64>::new_with_max(1000 * TRACKABLE_MAX, SIGFIG).unwrap();
+    let mut narrow_counts = Histogram::<u32>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    for v in &[1, TEST_VALUE_LEVEL, 1000 * TEST_VALUE_LEVEL, TRACKABLE_MAX] {
+        h.record_n(*v, 3).unwrap();
+        wide.record_n(*v, 3).unwrap();
+        narrow_counts.record_n(*v, 3).unwrap();
+    }
+
+    // different counts array lengths
+    assert_ne!(h.distinct_values(), wide.distinct_values());
+    assert_eq!(h, wide);
+    assert_eq!(h.fingerprint(), wide.fingerprint());
+    assert_eq!(h.fingerprint(), narrow_counts.fingerprint());
+    assert_eq!(h.fingerprint(), h.clone().fingerprint());
+
+    let empty = Histogram::<u64>::new(SIGFIG).unwrap();
+    assert_eq!(
+        empty.fingerprint(),
+        Histogram::<u8>::new_with_max(TRACKABLE_MAX, SIGFIG)
+            .unwrap()
+            .fingerprint()
+    );
+}
+
+#[test]
+fn fingerprint_distinct_histograms() {
+    let mut h = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    h.record_n(TEST_VALUE_LEVEL, 2).unwrap();
+    h.record(1000 * TEST_VALUE_LEVEL).unwrap();
+
+    let mut other_count = h.clone();
+    other_count.record(1000 * TEST_VALUE_LEVEL).unwrap();
+    assert_ne!(h.fingerprint(), other_count.fingerprint());
+
+    // same counts, swapped between values
+    let mut swapped = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    swapped.record(TEST_VALUE_LEVEL).unwrap();
+    swapped.record_n(1000 * TEST_VALUE_LEVEL, 2).unwrap();
+    assert_ne!(h.fingerprint(), swapped.fingerprint());
+
+    let mut other_sigfig = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG - 1).unwrap();
+    other_sigfig.add(&h).unwrap();
+    assert_ne!(h.fingerprint(), other_sigfig.fingerprint());
+
+    let empty = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG).unwrap();
+    assert_ne!(h.fingerprint(), empty.fingerprint());
+}
+
+#[test]
+fn counter_max_value() {
+    assert_eq!(u8::MAX, <u8 as Counter>::max_value());
+    assert_eq!(u16::MAX, <u16 as Counter>::max_value());
+    assert_eq!(u32::MAX, <u32 as Counter>::max_value());
+    assert_eq!(u64::MAX, <u64 as Counter>::max_value());
+    assert_eq!(u128::MAX, <u128 as Counter>::max_value());
+}
+
 #[test]
 fn total_count_overflow_from_add_same_dimensions_saturates_calculating_other_addend_total() {
     let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
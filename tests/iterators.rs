@@ -1,4 +1,6 @@
-use hdrhistogram::Histogram;
+use hdrhistogram::iterators::recorded::ValueRepr;
+use hdrhistogram::{Histogram, TopLevelBucket};
+use rand::{Rng, SeedableRng};
 
 #[test]
 fn iter_recorded_non_saturated_total_count() {
@@ -34,6 +36,157 @@ fn iter_recorded_saturated_total_count() {
     );
 }
 
+#[test]
+fn for_each_recorded_matches_iter_recorded() {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(7);
+    let mut h = Histogram::<u128>::new_with_bounds(1, u64::MAX, 3).unwrap();
+    for _ in 0..10_000 {
+        h.record_n(rng.gen_range(0..1_000_000), rng.gen_range(1..100))
+            .unwrap();
+    }
+    h.record_n(0, 5).unwrap();
+    h.record_n(u64::MAX, u128::MAX).unwrap();
+
+    let expected = h
+        .iter_recorded()
+        .map(|iv| {
+            (
+                iv.value_iterated_to(),
+                iv.count_at_value(),
+                iv.count_since_last_iteration(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut visited = Vec::new();
+    h.for_each_recorded(|value, count, since_last| visited.push((value, count, since_last)));
+    assert_eq!(expected, visited);
+
+    let empty = Histogram::<u64>::new(3).unwrap();
+    empty.for_each_recorded(|_, _, _| panic!("nothing recorded"));
+}
+
+#[test]
+fn iter_recorded_sparse_matches_non_zero_iter_all() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 5).unwrap();
+
+    h.record_n(1, 3).unwrap();
+    h.record(1_000_000_000).unwrap();
+    h.record_n(u64::MAX, 2).unwrap();
+
+    let expected = h
+        .iter_all()
+        .filter(|iv| iv.count_at_value() != 0)
+        .collect::<Vec<_>>();
+    assert_eq!(3, expected.len());
+    assert_eq!(expected, h.iter_recorded().collect::<Vec<_>>());
+}
+
+#[test]
+fn iter_recorded_repr_round_trips() {
+    let mut h = histo64(1024, 3_600_000_000, 2);
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    for _ in 0..10_000 {
+        h.record(rng.gen_range(0..3_600_000_000)).unwrap();
+    }
+
+    for &repr in &[ValueRepr::Lowest, ValueRepr::Median, ValueRepr::Highest] {
+        let mut h2 = histo64(1024, 3_600_000_000, 2);
+        for v in h.iter_recorded_repr(repr) {
+            let value = v.value_iterated_to();
+            let expected = match repr {
+                ValueRepr::Lowest => h.lowest_equivalent(value),
+                ValueRepr::Median => h.median_equivalent(value),
+                ValueRepr::Highest => h.highest_equivalent(value),
+            };
+            assert_eq!(expected, value);
+            h2.record_n(value, v.count_at_value()).unwrap();
+        }
+        assert_eq!(h, h2);
+        assert!(h.counts_eq(&h2));
+    }
+
+    assert_eq!(
+        h.iter_recorded().collect::<Vec<_>>(),
+        h.iter_recorded_repr(ValueRepr::Highest).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_cdf_monotonic_ending_at_one() {
+    let mut h = histo64(1, 3_600_000_000, 3);
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    for _ in 0..10_000 {
+        h.record(rng.gen_range(1..3_600_000_000)).unwrap();
+    }
+
+    let cdf = h.iter_cdf().collect::<Vec<_>>();
+    assert_eq!(h.iter_recorded().count(), cdf.len());
+    assert!(cdf.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1));
+    assert!(cdf.iter().all(|&(_, p)| p > 0.0 && p <= 1.0));
+    assert!((cdf.last().unwrap().1 - 1.0).abs() < 1e-12);
+    assert_eq!(h.max(), cdf.last().unwrap().0);
+}
+
+#[test]
+fn iter_top_level_buckets_two_buckets() {
+    let mut h = histo64(1, 4095, 3);
+    assert_eq!(2, h.buckets());
+
+    h.record_n(0, 2).unwrap();
+    h.record(1).unwrap();
+    h.record(2047).unwrap();
+    h.record_n(2048, 3).unwrap();
+    h.record(4095).unwrap();
+
+    assert_eq!(
+        vec![
+            TopLevelBucket {
+                index: 0,
+                low: 0,
+                high: 2047,
+                count: 4
+            },
+            TopLevelBucket {
+                index: 1,
+                low: 2048,
+                high: 4095,
+                count: 4
+            },
+        ],
+        h.iter_top_level_buckets().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_top_level_buckets_unit_magnitude_and_sum() {
+    let mut h = histo64(1024, 1_000_000_000, 2);
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+    for _ in 0..1000 {
+        h.record(rng.gen_range(0..1_000_000_000)).unwrap();
+    }
+
+    let buckets = h.iter_top_level_buckets().collect::<Vec<_>>();
+    assert_eq!(usize::from(h.buckets()), buckets.len());
+    assert_eq!(0, buckets[0].low);
+    assert_eq!(h.len(), buckets.iter().map(|b| b.count).sum::<u64>());
+    for (i, b) in buckets.iter().enumerate() {
+        assert_eq!(i, usize::from(b.index));
+        assert_eq!(b.count, h.count_between(b.low, b.high));
+        if i > 0 {
+            // contiguous, each twice as wide as the previous one (except the first)
+            assert_eq!(buckets[i - 1].high + 1, b.low);
+            assert_eq!(b.low, b.high - b.low + 1);
+        }
+    }
+}
+
+#[test]
+fn iter_cdf_empty() {
+    let h = histo64(1, 4095, 3);
+
+    assert_eq!(0, h.iter_cdf().count());
+}
+
 #[test]
 fn iter_linear_count_since_last_iteration_saturates() {
     let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
@@ -69,6 +222,63 @@ fn iter_linear_count_since_last_iteration_saturates() {
     );
 }
 
+#[test]
+fn iter_linear_value_sum_since_last_iteration() {
+    let mut h = Histogram::<u64>::new_with_max(1_000_000, 3).unwrap();
+    let pairs = [(3, 2), (99, 1), (100, 4), (101, 1), (450, 7), (999, 3)];
+    for &(v, c) in &pairs {
+        h.record_n(v, c).unwrap();
+    }
+
+    // values below 2048 have single-value buckets, so the sums are exact
+    let mut next_low = 0;
+    let mut steps = 0;
+    for v in h.iter_linear(100) {
+        let expected: u128 = pairs
+            .iter()
+            .filter(|&&(value, _)| value >= next_low && value <= v.value_iterated_to())
+            .map(|&(value, count)| u128::from(value) * u128::from(count))
+            .sum();
+        assert_eq!(expected, v.value_sum_since_last_iteration(), "{:?}", v);
+        next_low = v.value_iterated_to() + 1;
+        steps += 1;
+    }
+    assert_eq!(10, steps);
+
+    // in wider buckets, values are represented by their bucket's median equivalent
+    h += 123_456;
+    h += 654_321;
+    let total: u128 = h
+        .iter_linear(10_000)
+        .map(|v| v.value_sum_since_last_iteration())
+        .sum();
+    let expected: u128 = h
+        .iter_recorded()
+        .map(|v| {
+            u128::from(h.median_equivalent(v.value_iterated_to())) * u128::from(v.count_at_value())
+        })
+        .sum();
+    assert_eq!(expected, total);
+    assert_eq!(h.mean(), total as f64 / h.len() as f64);
+}
+
+#[test]
+fn iter_linear_zero_step_yields_nothing() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
+    assert_eq!(0, h.iter_linear(0).count());
+
+    h.record(1).unwrap();
+    h.record(1_000_000).unwrap();
+    assert_eq!(0, h.iter_linear(0).count());
+    assert!(h.iter_linear(0).next().is_none());
+
+    // the smallest valid step still works
+    assert_eq!(
+        h.iter_linear(1).last().map(|v| v.value_iterated_to()),
+        Some(h.highest_equivalent(1_000_000))
+    );
+}
+
 #[test]
 fn iter_linear_visits_buckets_wider_than_step_size_multiple_times() {
     let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
@@ -424,6 +634,25 @@ fn iter_logarithmic_bucket_values_min_1_base_10_all_buckets() {
     assert_eq!(expected, iter_values);
 }
 
+#[test]
+fn iter_logarithmic_invalid_parameters_yield_nothing() {
+    let h = prepare_histo_for_logarithmic_iterator();
+
+    assert_eq!(0, h.iter_log(1, 1.0).count());
+    assert_eq!(0, h.iter_log(1, 0.5).count());
+    assert_eq!(0, h.iter_log(1, -2.0).count());
+    assert_eq!(0, h.iter_log(1, f64::NAN).count());
+    assert_eq!(0, h.iter_log(0, 2.0).count());
+    assert_eq!(0, h.iter_log(0, 1.0).count());
+}
+
+#[test]
+fn iter_logarithmic_huge_base_terminates() {
+    let h = prepare_histo_for_logarithmic_iterator();
+
+    assert_eq!(2, h.iter_log(1, f64::INFINITY).count());
+}
+
 #[test]
 fn iter_linear_bucket_values_size_8_all_buckets() {
     // two buckets: 32 sub-buckets with scale 1, 16 with scale 2
@@ -865,6 +1094,21 @@ fn iter_quantiles_one_value() {
     assert_eq!(expected, iter_values);
 }
 
+#[test]
+fn iter_quantiles_zero_ticks_behaves_like_one() {
+    let mut h = histo64(1, 4095, 3);
+    for i in 1..1000 {
+        h.record(i).unwrap();
+    }
+
+    let zero: Vec<_> = h.iter_quantiles(0).collect();
+    let one: Vec<_> = h.iter_quantiles(1).collect();
+
+    assert!(!zero.is_empty());
+    assert_eq!(one, zero);
+    assert_eq!(1.0, zero.last().unwrap().quantile_iterated_to());
+}
+
 #[test]
 fn iter_quantiles_empty() {
     let h = histo64(1, 4095, 3);
@@ -872,6 +1116,158 @@ fn iter_quantiles_empty() {
     assert_eq!(0, h.iter_quantiles(2).count());
 }
 
+#[test]
+fn iter_quantiles_between_stays_within_range() {
+    let mut h = histo64(1, 100_000, 3);
+    for v in 1..=100_000 {
+        h.record(v).unwrap();
+    }
+
+    let values: Vec<_> = h.iter_quantiles_between(5, 0.9, 0.999).collect();
+
+    assert_eq!(0.9, values[0].quantile_iterated_to());
+    assert_eq!(h.value_at_quantile(0.9), values[0].value_iterated_to());
+    assert_eq!(0.999, values.last().unwrap().quantile_iterated_to());
+    assert!(values
+        .iter()
+        .all(|v| v.quantile_iterated_to() >= 0.9 && v.quantile_iterated_to() <= 0.999));
+    assert!(values
+        .windows(2)
+        .all(|w| w[0].quantile_iterated_to() < w[1].quantile_iterated_to()));
+    for v in &values {
+        assert_eq!(
+            h.value_at_quantile(v.quantile_iterated_to()),
+            v.value_iterated_to()
+        );
+    }
+}
+
+#[test]
+fn iter_quantiles_between_full_range_matches_iter_quantiles() {
+    let h = prepare_histo_for_logarithmic_iterator();
+
+    let between: Vec<_> = h.iter_quantiles_between(3, 0.0, 1.0).collect();
+    let all: Vec<_> = h.iter_quantiles(3).collect();
+
+    assert_eq!(all, between);
+}
+
+#[test]
+fn iter_quantiles_between_ends_at_last_value() {
+    let mut h = histo64(1, 4095, 3);
+    h.record_n(1, 10).unwrap();
+    h.record_n(1000, 10).unwrap();
+
+    let values: Vec<(u64, f64)> = h
+        .iter_quantiles_between(2, 0.6, 0.7)
+        .map(|v| (v.value_iterated_to(), v.quantile_iterated_to()))
+        .collect();
+
+    // the last non-zero index is reached right away, so skip straight to the end quantile
+    assert_eq!(vec![(1000, 0.6), (1000, 0.7)], values);
+}
+
+#[test]
+#[should_panic(expected = "Quantile range")]
+fn iter_quantiles_between_rejects_inverted_range() {
+    let h = histo64(1, 4095, 3);
+    let _ = h.iter_quantiles_between(2, 0.5, 0.4);
+}
+
+#[test]
+fn percentile_distribution_lines_one_per_quantile_step() {
+    let mut h = histo64(1, 3_600_000_000, 3);
+    for v in 1..=10_000 {
+        h.record(v * 1000).unwrap();
+    }
+
+    let lines: Vec<String> = h.percentile_distribution_lines(5, 1000.0).collect();
+
+    assert_eq!(h.iter_quantiles(5).count(), lines.len());
+    assert_eq!(
+        "       1.000 0.000000000000          1           1.00",
+        lines[0]
+    );
+    assert!(lines
+        .last()
+        .unwrap()
+        .ends_with(" 1.000000000000      10000"));
+}
+
+#[test]
+fn percentile_distribution_lines_empty() {
+    let h = histo64(1, 4095, 3);
+
+    assert_eq!(0, h.percentile_distribution_lines(5, 1.0).count());
+}
+
+#[test]
+fn write_percentile_distribution_scaled() {
+    let mut h = histo64(1, 3_600_000_000, 3);
+    for v in 1..=10_000 {
+        h.record(v * 1000).unwrap();
+    }
+
+    let mut out = Vec::new();
+    h.write_percentile_distribution(&mut out, 5, 1000.0)
+        .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+
+    assert_eq!(
+        "       Value     Percentile TotalCount 1/(1-Percentile)",
+        lines[0]
+    );
+    assert_eq!("", lines[1]);
+    assert_eq!(
+        "       1.000 0.000000000000          1           1.00",
+        lines[2]
+    );
+    assert_eq!(
+        "    1000.447 0.100000000000       1000           1.11",
+        lines[3]
+    );
+    assert_eq!(h.iter_quantiles(5).count() + 5, lines.len());
+    assert_eq!(
+        &[
+            "   10002.431 1.000000000000      10000",
+            "#[Mean    =     5000.505, StdDeviation   =     2886.752]",
+            "#[Max     =    10002.431, Total count    =        10000]",
+            "#[Buckets =           22, SubBuckets     =         2048]",
+        ],
+        &lines[lines.len() - 4..]
+    );
+}
+
+#[test]
+fn write_hgrm_matches_java_format() {
+    let mut h = histo64(1, 3_600_000_000, 3);
+    for v in 1..=10_000 {
+        h.record(v).unwrap();
+    }
+
+    let mut out = Vec::new();
+    h.write_hgrm(&mut out, 5).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.starts_with(concat!(
+        "       Value     Percentile TotalCount 1/(1-Percentile)\n",
+        "\n",
+        "       1.000 0.000000000000          1           1.00\n",
+    )));
+    assert!(out.ends_with(concat!(
+        "   10007.000 1.000000000000      10000\n",
+        "#[Mean    =     5000.898, StdDeviation   =     2886.893]\n",
+        "#[Max     =    10007.000, Total count    =        10000]\n",
+        "#[Buckets =           22, SubBuckets     =         2048]\n",
+    )));
+
+    let mut scaled = Vec::new();
+    h.write_percentile_distribution(&mut scaled, 5, 1.0)
+        .unwrap();
+    assert_eq!(out.as_bytes(), &scaled[..]);
+}
+
 fn prepare_histo_for_logarithmic_iterator() -> Histogram<u64> {
     // two buckets
     let mut h = histo64(1, 4095, 3);
@@ -907,3 +1303,45 @@ fn histo64(
     )
     .unwrap()
 }
+
+#[test]
+fn iter_all_rev_matches_reversed_iter_all() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, 10_000, 2).unwrap();
+    h.record_n(1, 3).unwrap();
+    h.record(100).unwrap();
+    h.record_n(5_000, 2).unwrap();
+
+    let mut expected = h.iter_all().collect::<Vec<_>>();
+    expected.reverse();
+    let actual = h.iter_all().rev().collect::<Vec<_>>();
+
+    assert_eq!(expected.len(), actual.len());
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn iter_all_from_both_ends_meets_in_the_middle() {
+    let mut h = Histogram::<u64>::new_with_bounds(1, 10_000, 2).unwrap();
+    h.record_n(1, 3).unwrap();
+    h.record(100).unwrap();
+    h.record_n(5_000, 2).unwrap();
+
+    let expected = h.iter_all().collect::<Vec<_>>();
+
+    let mut iter = h.iter_all();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some(v) = iter.next() {
+        front.push(v);
+        match iter.next_back() {
+            Some(v) => back.push(v),
+            None => break,
+        }
+    }
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+
+    back.reverse();
+    front.extend(back);
+    assert_eq!(expected, front);
+}
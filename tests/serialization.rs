@@ -1,7 +1,11 @@
 #[cfg(all(feature = "serialization", test))]
 mod tests {
-    use hdrhistogram::serialization::{Deserializer, Serializer, V2Serializer};
-    use hdrhistogram::Histogram;
+    use hdrhistogram::serialization::{
+        Base64DeserializeError, DeserializeError, Deserializer, Serializer, V2DeflateSerializer,
+        V2SerializeError, V2Serializer,
+    };
+    use hdrhistogram::{AdditionError, Histogram};
+    use rand::{Rng, SeedableRng};
 
     use std::fs::File;
     use std::io::{BufRead, BufReader, Read};
@@ -48,6 +52,296 @@ mod tests {
         assert_eq!(h, deser_h);
     }
 
+    // HdrHistogram_c writes cookies without the word size bits that the Java implementation sets,
+    // so the cookies are 0x1c849303 and 0x1c849304 rather than 0x1c849313 and 0x1c849314.
+    // This is laid out as `hdr_encode_compressed` produces it: bounds 1 to 3_600_000_000 with 3
+    // significant digits, containing 1, 1000 (x3), 123_456, and 3_000_000_000.
+    const C_COMPRESSED: [u8; 51] = [
+        0x1c, 0x84, 0x93, 0x04, 0x00, 0x00, 0x00, 0x2b, 0x78, 0xda, 0x93, 0x69, 0x99, 0xcc, 0xcc,
+        0xc0, 0xc0, 0xc0, 0xc3, 0x00, 0x01, 0xcc, 0x50, 0x9a, 0x11, 0x44, 0x5c, 0x9b, 0xbc, 0x84,
+        0xc1, 0xfe, 0x03, 0x54, 0x84, 0xe9, 0x34, 0x3f, 0xdb, 0xfe, 0x3c, 0xa6, 0xe9, 0x2f, 0x18,
+        0x99, 0x00, 0x9b, 0xe4, 0x08, 0x16,
+    ];
+
+    fn assert_c_histogram(h: &Histogram<u64>) {
+        assert_eq!(1, h.low());
+        assert_eq!(3_600_000_000, h.high());
+        assert_eq!(3, h.sigfig());
+        assert_eq!(6, h.len());
+        assert_eq!(1, h.count_at(1));
+        assert_eq!(3, h.count_at(1000));
+        assert_eq!(1, h.count_at(123_456));
+        assert_eq!(1, h.count_at(3_000_000_000));
+        assert_eq!(1, h.min());
+        assert!(h.equivalent(3_000_000_000, h.max()));
+    }
+
+    #[test]
+    fn deserialize_compressed_from_c_impl() {
+        let h: Histogram<u64> = Deserializer::new()
+            .deserialize(&mut &C_COMPRESSED[..])
+            .unwrap();
+
+        assert_c_histogram(&h);
+
+        // re-serializing with the Java-style cookie produces the same histogram
+        let mut serialized = Vec::new();
+        V2Serializer::new().serialize(&h, &mut serialized).unwrap();
+        let h2: Histogram<u64> = Deserializer::new()
+            .deserialize(&mut serialized.as_slice())
+            .unwrap();
+        assert_eq!(h, h2);
+    }
+
+    #[test]
+    fn deserialize_uncompressed_without_word_size_bits() {
+        let mut h = Histogram::<u64>::new_with_bounds(1, 3_600_000_000, 3).unwrap();
+        h.record(1).unwrap();
+        h.record_n(1000, 3).unwrap();
+        h.record(123_456).unwrap();
+        h.record(3_000_000_000).unwrap();
+
+        let mut serialized = Vec::new();
+        V2Serializer::new().serialize(&h, &mut serialized).unwrap();
+        // clear the word size bits like the C implementation does
+        serialized[3] &= !0xf0;
+        assert_eq!(&[0x1c, 0x84, 0x93, 0x03], &serialized[0..4]);
+
+        let h2: Histogram<u64> = Deserializer::new()
+            .deserialize(&mut serialized.as_slice())
+            .unwrap();
+        assert_c_histogram(&h2);
+    }
+
+    #[test]
+    fn deserialize_all_reads_until_eof() {
+        let mut buf = Vec::new();
+        let mut serializer = V2Serializer::new();
+        for i in 1..=3 {
+            let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+            h.record_n(i * 100, i).unwrap();
+            serializer.serialize(&h, &mut buf).unwrap();
+        }
+
+        let mut deserializer = Deserializer::new();
+        let histograms = deserializer
+            .deserialize_all::<u64, _>(&mut buf.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(3, histograms.len());
+        for (i, h) in (1..=3).zip(histograms.iter()) {
+            assert_eq!(i, h.len());
+            assert_eq!(i, h.count_at(i * 100));
+        }
+    }
+
+    #[test]
+    fn deserialize_counted_with_trailing_bytes() {
+        let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        h.record_n(42, 3).unwrap();
+        h.record(1_000_000).unwrap();
+        let trailing = [0xde, 0xad, 0xbe, 0xef];
+
+        let mut buf = Vec::new();
+        let written = V2Serializer::new().serialize(&h, &mut buf).unwrap();
+        buf.extend_from_slice(&trailing);
+        // header payload length, after the cookie
+        let payload_len = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+
+        let mut reader = buf.as_slice();
+        let (h2, read) = Deserializer::new()
+            .deserialize_counted::<u64, _>(&mut reader)
+            .unwrap();
+        assert_eq!(h, h2);
+        assert_eq!(40 + payload_len, read);
+        assert_eq!(written, read);
+        assert_eq!(&trailing[..], reader);
+
+        let mut buf = Vec::new();
+        let written = V2DeflateSerializer::new().serialize(&h, &mut buf).unwrap();
+        buf.extend_from_slice(&trailing);
+        let compressed_len = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+
+        let mut reader = buf.as_slice();
+        let (h2, read) = Deserializer::new()
+            .deserialize_counted::<u64, _>(&mut reader)
+            .unwrap();
+        assert_eq!(h, h2);
+        assert_eq!(8 + compressed_len, read);
+        assert_eq!(written, read);
+        assert_eq!(&trailing[..], reader);
+    }
+
+    #[test]
+    fn deserialize_all_empty() {
+        let mut deserializer = Deserializer::new();
+        let mut reader: &[u8] = &[];
+        let mut iter = deserializer.deserialize_all::<u64, _>(&mut reader);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn deserialize_all_truncated() {
+        let mut buf = Vec::new();
+        let mut serializer = V2Serializer::new();
+        let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        h.record(42).unwrap();
+        serializer.serialize(&h, &mut buf).unwrap();
+        serializer.serialize(&h, &mut buf).unwrap();
+        let len = buf.len();
+        buf.truncate(len - 1);
+
+        let mut deserializer = Deserializer::new();
+        let mut reader = buf.as_slice();
+        let mut iter = deserializer.deserialize_all::<u64, _>(&mut reader);
+
+        assert_eq!(h, iter.next().unwrap().unwrap());
+        match iter.next() {
+            Some(Err(DeserializeError::IoError(e))) => {
+                assert_eq!(std::io::ErrorKind::UnexpectedEof, e.kind())
+            }
+            r => panic!("unexpected {:?}", r.map(|r| r.map(|h| h.len()))),
+        }
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn serialize_to_slice_matches_vec() {
+        let h = load_histogram_from_num_per_line(Path::new("tests/data/seq-nums.txt"));
+        let mut serializer = V2Serializer::new();
+
+        let mut vec = Vec::new();
+        let len = serializer.serialize(&h, &mut vec).unwrap();
+
+        // large enough for the worst case
+        let mut buf = vec![0; V2Serializer::max_encoded_len(&h).unwrap()];
+        assert_eq!(len, serializer.serialize_to_slice(&h, &mut buf).unwrap());
+        assert_eq!(vec, &buf[..len]);
+
+        // exactly large enough
+        let mut buf = vec![0; len];
+        assert_eq!(len, serializer.serialize_to_slice(&h, &mut buf).unwrap());
+        assert_eq!(vec, buf);
+    }
+
+    #[test]
+    fn serialize_to_slice_too_small() {
+        let h = load_histogram_from_num_per_line(Path::new("tests/data/seq-nums.txt"));
+        let mut serializer = V2Serializer::new();
+        let len = serializer.serialize(&h, &mut Vec::new()).unwrap();
+
+        let mut buf = vec![0; len - 1];
+        match serializer.serialize_to_slice(&h, &mut buf) {
+            Err(V2SerializeError::BufferTooSmall) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn deserialize_and_add_matches_deserialize_then_add() {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+        let mut same = Histogram::<u64>::new(3).unwrap();
+        let mut wider = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        let mut other_config = Histogram::<u64>::new_with_bounds(1000, u64::MAX, 2).unwrap();
+        for h in &mut [&mut same, &mut wider, &mut other_config] {
+            for _ in 0..1000 {
+                h.record(rng.gen_range(0..1_000_000_000_000)).unwrap();
+            }
+            h.record(0).unwrap();
+        }
+
+        let mut buf = Vec::new();
+        V2Serializer::new().serialize(&same, &mut buf).unwrap();
+        V2DeflateSerializer::new()
+            .serialize(&wider, &mut buf)
+            .unwrap();
+        V2Serializer::new()
+            .serialize(&other_config, &mut buf)
+            .unwrap();
+
+        let mut deserializer = Deserializer::new();
+        let mut expected = Histogram::<u64>::new(3).unwrap();
+        let mut reader = buf.as_slice();
+        for _ in 0..3 {
+            let h: Histogram<u64> = deserializer.deserialize(&mut reader).unwrap();
+            expected.add(h).unwrap();
+        }
+
+        let mut accumulator = Histogram::<u64>::new(3).unwrap();
+        let mut reader = buf.as_slice();
+        for _ in 0..3 {
+            deserializer
+                .deserialize_and_add(&mut reader, &mut accumulator)
+                .unwrap();
+        }
+        assert!(reader.is_empty());
+
+        assert_eq!(expected, accumulator);
+        assert_eq!(expected.len(), accumulator.len());
+        assert_eq!(expected.min(), accumulator.min());
+        assert_eq!(expected.min_nz(), accumulator.min_nz());
+        assert_eq!(expected.max(), accumulator.max());
+        assert_eq!(expected.high(), accumulator.high());
+    }
+
+    #[test]
+    fn deserialize_and_add_out_of_range_leaves_accumulator_unchanged() {
+        let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        h.record(10).unwrap();
+        h.record(1_000_000_000).unwrap();
+        let mut buf = Vec::new();
+        V2Serializer::new().serialize(&h, &mut buf).unwrap();
+
+        let mut accumulator = Histogram::<u64>::new_with_max(1_000_000, 3).unwrap();
+        accumulator.record(100).unwrap();
+        let before = accumulator.clone();
+
+        match Deserializer::new().deserialize_and_add(&mut buf.as_slice(), &mut accumulator) {
+            Err(DeserializeError::AdditionFailed(AdditionError::OtherAddendValueExceedsRange)) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        assert_eq!(before, accumulator);
+        assert_eq!(1, accumulator.len());
+
+        // a count too big for the accumulator's counter type
+        let mut h = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        h.record_n(10, 1000).unwrap();
+        let mut buf = Vec::new();
+        V2Serializer::new().serialize(&h, &mut buf).unwrap();
+
+        let mut small = Histogram::<u8>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        small.record_n(20, 100).unwrap();
+        match Deserializer::new().deserialize_and_add(&mut buf.as_slice(), &mut small) {
+            Err(DeserializeError::UnsuitableCounterType) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        assert_eq!(0, small.count_at(10));
+        assert_eq!(100, small.len());
+    }
+
+    #[test]
+    fn deserialize_and_add_saturates_like_add() {
+        let mut h = Histogram::<u8>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        h.record_n(10, 200).unwrap();
+        let mut buf = Vec::new();
+        V2Serializer::new().serialize(&h, &mut buf).unwrap();
+
+        let mut accumulator = Histogram::<u8>::new_with_bounds(1, u64::MAX, 3).unwrap();
+        accumulator.record_n(10, 100).unwrap();
+        let mut expected = accumulator.clone();
+        expected.add(&h).unwrap();
+
+        Deserializer::new()
+            .deserialize_and_add(&mut buf.as_slice(), &mut accumulator)
+            .unwrap();
+        assert_eq!(255, accumulator.count_at(10));
+        assert_eq!(expected, accumulator);
+        assert_eq!(300, accumulator.len());
+    }
+
     #[test]
     fn total_count_overflow_from_deserialize_saturates() {
         let mut h = Histogram::<u64>::new_with_bounds(1, u64::max_value(), 3).unwrap();
@@ -67,6 +361,107 @@ mod tests {
         assert_eq!(u64::max_value(), deser_h.len());
     }
 
+    #[test]
+    fn max_encoded_len_is_upper_bound() {
+        let empty = Histogram::<u64>::new_with_bounds(1, u64::MAX, 3).unwrap();
+
+        let mut full = Histogram::<u64>::new_with_max(10_000, 3).unwrap();
+        for v in 0..=10_000 {
+            // large counts so that every count uses a long varint
+            full.record_n(v, 1 << 56).unwrap();
+        }
+
+        let mut sparse = Histogram::<u32>::new(2).unwrap();
+        sparse.record(1).unwrap();
+        sparse.record(1 << 40).unwrap();
+
+        let seq = load_histogram_from_num_per_line(Path::new("tests/data/seq-nums.txt"));
+
+        let mut vec = Vec::new();
+        for h in &[empty, full, seq] {
+            vec.clear();
+            let len = V2Serializer::new().serialize(h, &mut vec).unwrap();
+            assert_eq!(len, vec.len());
+            assert!(len <= V2Serializer::max_encoded_len(h).unwrap());
+        }
+
+        vec.clear();
+        let len = V2Serializer::new().serialize(&sparse, &mut vec).unwrap();
+        assert!(len <= V2Serializer::max_encoded_len(&sparse).unwrap());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn serialize_many_parallel_matches_sequential() {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(7);
+        let histograms: Vec<Histogram<u64>> = (0..64)
+            .map(|i| {
+                let mut h = Histogram::<u64>::new(3).unwrap();
+                for _ in 0..(i * 10) {
+                    h += rng.gen_range(0..1_u64 << (i % 60 + 1));
+                }
+                h
+            })
+            .collect();
+
+        let parallel = hdrhistogram::serialization::serialize_many_parallel(&histograms).unwrap();
+
+        assert_eq!(histograms.len(), parallel.len());
+        let mut serializer = V2Serializer::new();
+        for (h, bytes) in histograms.iter().zip(parallel.iter()) {
+            let mut sequential = Vec::new();
+            let _ = serializer.serialize(h, &mut sequential).unwrap();
+            assert_eq!(&sequential, bytes);
+        }
+    }
+
+    #[test]
+    fn record_pairs_bulk_zero_only_round_trips() {
+        let mut bulk = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+        bulk.record_pairs_bulk(&[(0, 5)]).unwrap();
+
+        let mut per_pair = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+        per_pair.record_n(0, 5).unwrap();
+
+        assert_eq!(per_pair, bulk);
+        assert_eq!(0, bulk.max());
+        assert_eq!(0, bulk.min());
+
+        let mut buf = Vec::new();
+        let _ = V2Serializer::new().serialize(&bulk, &mut buf).unwrap();
+        let deser: Histogram<u64> = Deserializer::new()
+            .deserialize(&mut buf.as_slice())
+            .unwrap();
+        assert_eq!(bulk, deser);
+        assert_eq!(5, deser.count_at(0));
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let mut h = Histogram::<u64>::new_with_max(3_600_000_000, 3).unwrap();
+        h.record_n(1, 7).unwrap();
+        h.record(1_000_000).unwrap();
+        h.record(3_600_000_000).unwrap();
+
+        let encoded = h.to_base64().unwrap();
+        let decoded = Histogram::<u64>::from_base64(&encoded).unwrap();
+        assert_eq!(h, decoded);
+    }
+
+    #[test]
+    fn base64_malformed_input_errors() {
+        match Histogram::<u64>::from_base64("not base64!!") {
+            Err(Base64DeserializeError::InvalidBase64) => {}
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        // valid base64, but not a histogram
+        match Histogram::<u64>::from_base64("AAAAAAAAAAA=") {
+            Err(Base64DeserializeError::DeserializeError(_)) => {}
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
     fn load_histogram_from_num_per_line(path: &Path) -> Histogram<u64> {
         // max is Java's Long.MAX_VALUE
         let mut h: Histogram<u64> =
@@ -19,6 +19,29 @@ mod sync {
         assert_eq!(h.len(), 1);
     }
 
+    #[test]
+    fn record_directly_and_through_recorder() {
+        let mut h: SyncHistogram<_> = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG)
+            .unwrap()
+            .into();
+        h.record(TEST_VALUE_LEVEL).unwrap();
+
+        let mut r = h.recorder();
+        let jh = thread::spawn(move || {
+            r += 1000 * TEST_VALUE_LEVEL;
+        });
+
+        // visible without a refresh
+        h.record(TEST_VALUE_LEVEL).unwrap();
+        assert_eq!(h.count_at(TEST_VALUE_LEVEL), 2);
+
+        h.refresh();
+        jh.join().unwrap();
+        assert_eq!(h.count_at(TEST_VALUE_LEVEL), 2);
+        assert_eq!(h.count_at(1000 * TEST_VALUE_LEVEL), 1);
+        assert_eq!(h.len(), 3);
+    }
+
     #[test]
     fn recorder_drop() {
         let mut h: SyncHistogram<_> = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG)
@@ -69,6 +92,28 @@ mod sync {
         assert_eq!(h.len(), 1);
     }
 
+    #[test]
+    fn flush_without_phase() {
+        let mut h: SyncHistogram<_> = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG)
+            .unwrap()
+            .into();
+        let mut r = h.recorder();
+        r += TEST_VALUE_LEVEL;
+        r.flush();
+
+        // the recorder never observes the phase change, but the flushed sample is still visible
+        h.refresh_timeout(time::Duration::from_millis(100));
+        assert_eq!(h.count_at(TEST_VALUE_LEVEL), 1);
+        assert_eq!(h.len(), 1);
+
+        // nothing is sent twice
+        r += TEST_VALUE_LEVEL;
+        drop(r);
+        h.refresh();
+        assert_eq!(h.count_at(TEST_VALUE_LEVEL), 2);
+        assert_eq!(h.len(), 2);
+    }
+
     #[test]
     fn recorder_drop_staged() {
         let mut h: SyncHistogram<_> = Histogram::<u64>::new_with_max(TRACKABLE_MAX, SIGFIG)